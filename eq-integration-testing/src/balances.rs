@@ -22,10 +22,9 @@ pub trait Balances: System {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
-pub struct AccountStore<T: Balances> {
-    #[store(returns = eq_balances::SignedBalance<<T as Balances>::Balance>)]
+pub struct AccountsStore<T: Balances> {
+    #[store(returns = eq_balances::AccountData<<T as Balances>::Balance>)]
     pub account_id: T::AccountId,
-    pub currency: eq_balances::currency::Currency,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Store, Encode)]
@@ -1,4 +1,4 @@
-use crate::balances::{AccountStoreExt, BurnCall, DepositCall};
+use crate::balances::{AccountsStoreExt, BurnCall, DepositCall};
 use crate::key::{AccountKey, DevNonces, DevPubKey};
 use crate::keyring::{NonceManager, PubKey};
 use crate::requester::{sudo_call_chain, ChainCallSuccess};
@@ -199,8 +199,9 @@ pub async fn set_balance(
     sudo: &String
 ) -> Result<Vec<ChainCallSuccess>, Box<dyn std::error::Error>> {
     let curr = client
-        .account(account_key.acc_id(), currency, Option::None)
+        .accounts(account_key.acc_id(), Option::None)
         .await?
+        .balance(&currency)
         .to_i128();
     let delta = balance.to_i128() - curr;
     println!(
@@ -1,5 +1,5 @@
 use super::runtime::AccountId;
-use crate::balances::{AccountStoreExt, BalancesAggregatesStoreExt};
+use crate::balances::{AccountsStoreExt, BalancesAggregatesStoreExt};
 use crate::claim::ClaimsStoreExt;
 use crate::claim::PreclaimsStoreExt;
 use crate::claim::SigningStoreExt;
@@ -10,6 +10,7 @@ use crate::vesting::VestedStoreExt;
 use crate::vesting::VestingStoreExt;
 use core::slice::Iter;
 use eq_balances::{currency::Currency, BalancesAggregate, SignedBalance};
+use eq_primitives::amount::CurrencyAmount;
 use serde::ser::SerializeTupleVariant;
 use serde::{Serialize, Serializer};
 use sp_arithmetic::{FixedI64, FixedPointNumber};
@@ -100,24 +101,31 @@ impl Serialize for AccountName {
 #[derive(Debug, PartialEq, Serialize)]
 pub struct BalanceData {
     pub account: AccountName,
-    pub balances: Vec<(Currency, SignedBalance<u64>)>,
+    /// Balances like `1.5 ETH`, debts are prefixed with `-`
+    pub balances: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct AggregateData {
+    total_issuance: CurrencyAmount,
+    total_debt: CurrencyAmount,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
 pub struct VestingInfo {
-    locked: u64,
-    per_block: u64,
+    locked: CurrencyAmount,
+    per_block: CurrencyAmount,
     starting_block: u64,
 }
 
 #[derive(Debug, PartialEq, Serialize)]
 pub struct MyTestData {
     pub balances: Vec<BalanceData>,
-    pub balance_aggregates: Vec<(Currency, BalancesAggregate<u64>)>,
+    pub balance_aggregates: Vec<AggregateData>,
     pub vesting: Vec<(AccountId, VestingInfo)>,
-    pub vested: Vec<(AccountId, u64)>,
-    pub claims: Vec<(eq_claim::EthereumAddress, u64)>,
-    pub total: u64,
+    pub vested: Vec<(AccountId, CurrencyAmount)>,
+    pub claims: Vec<(eq_claim::EthereumAddress, CurrencyAmount)>,
+    pub total: CurrencyAmount,
     pub claim_vesting: Vec<(eq_claim::EthereumAddress, u64, u64, u64)>,
     pub signing: Vec<(eq_claim::EthereumAddress, bool)>,
     pub preclaims: Vec<(AccountId, eq_claim::EthereumAddress)>,
@@ -174,6 +182,20 @@ pub fn cmp_currencies(c1: &Currency, c2: &Currency) -> Ordering {
     c1.value().cmp(&c2.value())
 }
 
+/// Vesting and claims are paid in the basic currency
+fn basic_amount(amount: u64) -> CurrencyAmount {
+    CurrencyAmount::new(Currency::Eq, amount)
+}
+
+fn humanize_signed_balance(currency: &Currency, balance: &SignedBalance<u64>) -> String {
+    match balance {
+        SignedBalance::Positive(amount) => CurrencyAmount::new(*currency, *amount).to_string(),
+        SignedBalance::Negative(amount) => {
+            format!("-{}", CurrencyAmount::new(*currency, *amount))
+        }
+    }
+}
+
 pub fn humanize_balances(
     balances: &HashMap<AccountId, HashMap<Currency, SignedBalance<u64>>>,
     pub_key_store: &PubKeyStore,
@@ -183,12 +205,15 @@ pub fn humanize_balances(
         .map(|(id, bs)| {
             // let account_option = Keyring::from_account_id(&id);
 
-            let mut blncs: Vec<_> = bs.iter().map(|(c, b)| (*c, b.clone())).collect();
+            let mut blncs: Vec<_> = bs.iter().collect();
             blncs.sort_by(|(c1, _), (c2, _)| cmp_currencies(&c1, &c2));
 
             BalanceData {
                 account: acc_id_to_acc_name(id, pub_key_store),
-                balances: blncs,
+                balances: blncs
+                    .into_iter()
+                    .map(|(c, b)| humanize_signed_balance(c, b))
+                    .collect(),
             }
         })
         .collect();
@@ -200,11 +225,16 @@ pub fn humanize_balances(
 
 pub fn humanize_balance_aggregates(
     aggregates: &HashMap<Currency, BalancesAggregate<u64>>,
-) -> Vec<(Currency, BalancesAggregate<u64>)> {
-    let mut aggs: Vec<_> = aggregates.iter().map(|(c, a)| (*c, a.clone())).collect();
+) -> Vec<AggregateData> {
+    let mut aggs: Vec<_> = aggregates.iter().collect();
     aggs.sort_by(|(c1, _), (c2, _)| cmp_currencies(&c1, &c2));
 
-    aggs
+    aggs.into_iter()
+        .map(|(c, a)| AggregateData {
+            total_issuance: CurrencyAmount::new(*c, a.total_issuance),
+            total_debt: CurrencyAmount::new(*c, a.total_debt),
+        })
+        .collect()
 }
 
 fn acc_id_to_acc_name(acc_id: &AccountId, pub_key_store: &PubKeyStore) -> AccountName {
@@ -224,8 +254,8 @@ pub fn humanize_vesting(
             (
                 a.clone(),
                 VestingInfo {
-                    locked: p.locked,
-                    per_block: p.per_block,
+                    locked: basic_amount(p.locked),
+                    per_block: basic_amount(p.per_block),
                     starting_block: p.per_block,
                 },
             )
@@ -234,19 +264,22 @@ pub fn humanize_vesting(
     vst
 }
 
-pub fn humanize_vested(vested: &HashMap<AccountId, u64>) -> Vec<(AccountId, u64)> {
-    let vst: Vec<_> = vested.iter().map(|(a, p)| (a.clone(), p.clone())).collect();
+pub fn humanize_vested(vested: &HashMap<AccountId, u64>) -> Vec<(AccountId, CurrencyAmount)> {
+    let vst: Vec<_> = vested
+        .iter()
+        .map(|(a, p)| (a.clone(), basic_amount(*p)))
+        .collect();
     // vst.sort_by(|b1, b2| b1.account.cmp(&b2.account));
     vst
 }
 
 pub fn humanize_claims(
     claims: &HashMap<eq_claim::EthereumAddress, u64>,
-) -> Vec<(eq_claim::EthereumAddress, u64)> {
-    let mut clm: Vec<_> = claims.iter().map(|(a, p)| (a.clone(), p.clone())).collect();
+) -> Vec<(eq_claim::EthereumAddress, CurrencyAmount)> {
+    let mut clm: Vec<_> = claims.iter().map(|(a, p)| (a.clone(), *p)).collect();
     clm.sort_by(|b1, b2| b1.partial_cmp(b2).unwrap());
 
-    clm
+    clm.into_iter().map(|(a, p)| (a, basic_amount(p))).collect()
 }
 
 pub fn humanize_claim_vesting(
@@ -288,7 +321,7 @@ pub fn humanize_test_data(test_data: &TestData, pub_key_store: &PubKeyStore) ->
         vesting: humanize_vesting(&test_data.vesting),
         vested: humanize_vested(&test_data.vested),
         claims: humanize_claims(&test_data.claims),
-        total: test_data.total,
+        total: basic_amount(test_data.total),
         claim_vesting: humanize_claim_vesting(&test_data.claim_vesting),
         signing: humanize_signing(&test_data.signing),
         preclaims: humanize_preclaims(&test_data.preclaims),
@@ -485,7 +518,7 @@ impl TestContext {
         println!("read_storage:1");
         let mut data = TestData::new();
         let mut prefix = twox_128(b"EqBalances").to_vec();
-        prefix.extend(twox_128(b"Account").to_vec());
+        prefix.extend(twox_128(b"Accounts").to_vec());
         let mut processed_accounts: HashSet<AccountId> = HashSet::new();
         let mut processed_currencies: HashMap<AccountId, HashSet<Currency>> = HashMap::new();
 
@@ -495,8 +528,8 @@ impl TestContext {
         let end_account_id_i = start_account_id_i + 32;
 
         println!("read_storage:2");
-        let mut iter = client.account_iter(None).await?;
-        while let Some((key, signed_balance)) = iter.next().await? {
+        let mut iter = client.accounts_iter(None).await?;
+        while let Some((key, account_data)) = iter.next().await? {
             // log::info!("key encoded: {:?}", hex::encode(&key.0));
             // log::info!("key encoded: {:?}", key.0);
             let acc_id: Vec<_> = key
@@ -506,21 +539,20 @@ impl TestContext {
                 .collect();
             let acc_id = hex::encode(&acc_id);
             let acc_id = AccountId::from_str(&acc_id).unwrap();
-            let currency = Currency::from(key.0[key.0.len() - 1]);
-            let acc_proc_currencies = processed_currencies
-                .entry(acc_id.clone())
-                .or_insert(HashSet::new());
-            acc_proc_currencies.insert(currency);
-            // log::info!("acc_id: {:?}", acc_id);
-            // log::info!("currency num: {:?}", key.0[key.0.len() - 1]);
-            // log::info!("currency: {:?}", currency);
-            // log::info!("signed_balance: {:?}\n", signed_balance);
-            // balances
-            let acc_balance = data
-                .balances
-                .entry(acc_id.clone())
-                .or_insert(HashMap::new());
-            acc_balance.entry(currency).or_insert(signed_balance);
+            for (currency, signed_balance) in account_data.balances() {
+                let acc_proc_currencies = processed_currencies
+                    .entry(acc_id.clone())
+                    .or_insert(HashSet::new());
+                acc_proc_currencies.insert(*currency);
+                // balances
+                let acc_balance = data
+                    .balances
+                    .entry(acc_id.clone())
+                    .or_insert(HashMap::new());
+                acc_balance
+                    .entry(*currency)
+                    .or_insert(signed_balance.clone());
+            }
             processed_accounts.insert(acc_id.clone());
         }
         println!("read_storage:3");
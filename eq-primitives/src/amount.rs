@@ -0,0 +1,244 @@
+//! Human readable currency amounts like `1.5 ETH`, used by tooling outside the runtime
+
+use crate::currency::{Currency, ParseCurrencyError};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+/// Amount of the currency in its smallest units, printed and parsed with the currency
+/// decimals
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CurrencyAmount {
+    pub currency: Currency,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl CurrencyAmount {
+    /// Amount with the decimals of the currency
+    pub fn new(currency: Currency, amount: u64) -> Self {
+        Self::with_decimals(currency, amount, currency.decimals())
+    }
+
+    /// Amount with decimals taken from elsewhere, e.g. the asset registry
+    pub fn with_decimals(currency: Currency, amount: u64, decimals: u8) -> Self {
+        CurrencyAmount {
+            currency,
+            amount,
+            decimals,
+        }
+    }
+}
+
+/// Error of parsing a currency amount
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseAmountError {
+    /// Not a `<number> <ticker>` string
+    InvalidFormat,
+    /// Ticker is not known
+    InvalidCurrency(ParseCurrencyError),
+    /// More fraction digits than the currency decimals, the amount can't be stored
+    /// without rounding
+    TooManyDecimals,
+    /// Amount doesn't fit into `u64` units
+    Overflow,
+}
+
+impl fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseAmountError::InvalidFormat => f.write_str("expected `<number> <ticker>`"),
+            ParseAmountError::InvalidCurrency(e) => e.fmt(f),
+            ParseAmountError::TooManyDecimals => f.write_str("too many fraction digits"),
+            ParseAmountError::Overflow => f.write_str("amount overflow"),
+        }
+    }
+}
+
+impl std::error::Error for ParseAmountError {}
+
+impl From<ParseCurrencyError> for ParseAmountError {
+    fn from(e: ParseCurrencyError) -> Self {
+        ParseAmountError::InvalidCurrency(e)
+    }
+}
+
+/// Prints the shortest exact decimal form, e.g. `1.5 ETH` or `2 BTC`
+impl fmt::Display for CurrencyAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits = format!(
+            "{:0>width$}",
+            self.amount,
+            width = self.decimals as usize + 1
+        );
+        let (integer, fraction) = digits.split_at(digits.len() - self.decimals as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            write!(f, "{} {}", integer, self.currency)
+        } else {
+            write!(f, "{}.{} {}", integer, fraction, self.currency)
+        }
+    }
+}
+
+/// Parses `<number> <ticker>` strings with the decimals of the currency
+impl FromStr for CurrencyAmount {
+    type Err = ParseAmountError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let (number, ticker) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(number), Some(ticker), None) => (number, ticker),
+            _ => return Err(ParseAmountError::InvalidFormat),
+        };
+        let currency = Currency::from_str(ticker)?;
+        let amount = parse_units(number, currency.decimals())?;
+        Ok(CurrencyAmount::new(currency, amount))
+    }
+}
+
+/// Parses a decimal number into units of `10^-decimals`
+pub fn parse_units(number: &str, decimals: u8) -> Result<u64, ParseAmountError> {
+    let (integer, fraction) = match number.find('.') {
+        Some(i) => (&number[..i], &number[i + 1..]),
+        None => (number, ""),
+    };
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) {
+        return Err(ParseAmountError::InvalidFormat);
+    }
+    if number.ends_with('.') {
+        return Err(ParseAmountError::InvalidFormat);
+    }
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(ParseAmountError::TooManyDecimals);
+    }
+
+    let scale = 10u64
+        .checked_pow(decimals as u32)
+        .ok_or(ParseAmountError::Overflow)?;
+    let integer = integer
+        .parse::<u64>()
+        .map_err(|_| ParseAmountError::Overflow)?;
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        fraction
+            .parse::<u64>()
+            .map_err(|_| ParseAmountError::Overflow)?
+            * 10u64.pow((decimals as usize - fraction.len()) as u32)
+    };
+    integer
+        .checked_mul(scale)
+        .and_then(|units| units.checked_add(fraction))
+        .ok_or(ParseAmountError::Overflow)
+}
+
+impl Serialize for CurrencyAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CurrencyAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+mod test {
+    #![cfg(test)]
+
+    use super::*;
+
+    #[test]
+    fn display() {
+        let cases = [
+            (Currency::Eth, 1_500_000_000, "1.5 ETH"),
+            (Currency::Btc, 2_000_000_000, "2 BTC"),
+            (Currency::Eq, 1, "0.000000001 EQ"),
+            (Currency::Usd, 0, "0 USD"),
+            (Currency::Other(100), 10_000_000_001, "10.000000001 #100"),
+            (Currency::Eos, u64::MAX, "18446744073.709551615 EOS"),
+        ];
+        for (currency, amount, expected) in cases.iter() {
+            assert_eq!(
+                CurrencyAmount::new(*currency, *amount).to_string(),
+                *expected
+            );
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let cases = [
+            ("1.5 ETH", Currency::Eth, 1_500_000_000),
+            ("1.50 eth", Currency::Eth, 1_500_000_000),
+            ("  2   BTC ", Currency::Btc, 2_000_000_000),
+            ("0.000000001 EQ", Currency::Eq, 1),
+            ("007 #100", Currency::Other(100), 7_000_000_000),
+            ("18446744073.709551615 EOS", Currency::Eos, u64::MAX),
+        ];
+        for (s, currency, amount) in cases.iter() {
+            assert_eq!(
+                s.parse::<CurrencyAmount>(),
+                Ok(CurrencyAmount::new(*currency, *amount)),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            ("1.5", ParseAmountError::InvalidFormat),
+            ("1.5 ETH BTC", ParseAmountError::InvalidFormat),
+            (".5 ETH", ParseAmountError::InvalidFormat),
+            ("1. ETH", ParseAmountError::InvalidFormat),
+            ("-1 ETH", ParseAmountError::InvalidFormat),
+            ("1e9 ETH", ParseAmountError::InvalidFormat),
+            (
+                "1 DOGE",
+                ParseAmountError::InvalidCurrency(ParseCurrencyError),
+            ),
+            ("0.0000000001 ETH", ParseAmountError::TooManyDecimals),
+            ("18446744073.709551616 EOS", ParseAmountError::Overflow),
+            ("99999999999999999999 EOS", ParseAmountError::Overflow),
+        ];
+        for (s, error) in cases.iter() {
+            assert_eq!(s.parse::<CurrencyAmount>(), Err(*error), "{}", s);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for amount in [
+            0,
+            1,
+            10,
+            999_999_999,
+            1_000_000_000,
+            123_456_789_012,
+            u64::MAX,
+        ]
+        .iter()
+        {
+            let value = CurrencyAmount::new(Currency::Btc, *amount);
+            assert_eq!(value.to_string().parse::<CurrencyAmount>(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn serde() {
+        let value = CurrencyAmount::new(Currency::Eth, 1_500_000_000);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"1.5 ETH\"");
+        assert_eq!(
+            serde_json::from_str::<CurrencyAmount>(&json).unwrap(),
+            value
+        );
+    }
+}
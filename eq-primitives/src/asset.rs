@@ -0,0 +1,59 @@
+use crate::currency::Currency;
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::RuntimeDebug;
+use sp_std::prelude::*;
+
+/// Listing status of the asset
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "lowercase"))]
+pub enum AssetStatus {
+    /// Asset can be transferred, deposited and withdrawn
+    Active,
+    /// Balances are kept, but can't be changed
+    Frozen,
+    /// Asset is not traded anymore
+    Delisted,
+}
+
+impl Default for AssetStatus {
+    fn default() -> AssetStatus {
+        AssetStatus::Active
+    }
+}
+
+/// Registry entry of the asset
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Default, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct AssetData<Balance> {
+    /// Ticker of the asset, e.g. `b"BTC"`
+    pub symbol: Vec<u8>,
+    /// Number of decimals of the asset amounts
+    pub decimals: u8,
+    /// Minimum balance of the account in the asset
+    pub existential_deposit: Balance,
+    pub status: AssetStatus,
+}
+
+/// Source of the listed currencies
+pub trait AssetGetter<Balance> {
+    /// Registry entry of the currency, `None` for currencies that are not listed
+    fn get_asset(currency: &Currency) -> Option<AssetData<Balance>>;
+    /// All listed currencies
+    fn currencies() -> Vec<Currency>;
+}
+
+/// Built-in currencies without registry metadata, for mocks and chains without the
+/// asset registry. Currencies added later are only listed by the registry.
+impl<Balance> AssetGetter<Balance> for () {
+    fn get_asset(_currency: &Currency) -> Option<AssetData<Balance>> {
+        None
+    }
+
+    fn currencies() -> Vec<Currency> {
+        Currency::iterator_with_usd().cloned().collect()
+    }
+}
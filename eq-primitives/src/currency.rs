@@ -1,30 +1,41 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::slice::Iter;
-use frame_support::codec::{Decode, Encode};
+use core::str::FromStr;
+use frame_support::codec::{Decode, Encode, EncodeLike, Error, Input, Output};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 use sp_runtime::RuntimeDebug;
 
-#[derive(Encode, Decode, Clone, Copy, PartialEq, RuntimeDebug, Hash)]
+/// Currency identifier, encoded as a single byte. Built-in currencies have their own
+/// variants, currencies listed later in the asset registry use `Other`. Currencies are
+/// compared, hashed and serialized by id, so `Other(1)` is the same currency as `Usd`.
+#[derive(Clone, Copy, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[repr(u8)]
+#[cfg_attr(feature = "std", serde(from = "CurrencyRepr", into = "CurrencyRepr"))]
 pub enum Currency {
-    Unknown = 0,
-    Usd = 1,
-    Eq = 2,
-    Eth = 3,
-    Btc = 4,
-    Eos = 5,
+    Unknown,
+    Usd,
+    Eq,
+    Eth,
+    Btc,
+    Eos,
+    /// Currency without a built-in variant. Decoding and deserializing map built-in ids
+    /// to their named variants, so `Other` never holds them.
+    Other(u8),
 }
 
 impl Currency {
+    /// Built-in currencies except `Usd`
     pub fn iterator() -> Iter<'static, Currency> {
         static CURRENCIES: [Currency; 4] =
             [Currency::Eq, Currency::Eth, Currency::Btc, Currency::Eos];
         CURRENCIES.iter()
     }
 
+    /// All built-in currencies
     pub fn iterator_with_usd() -> Iter<'static, Currency> {
         static CURRENCIES: [Currency; 5] = [
             Currency::Eq,
@@ -37,15 +48,96 @@ impl Currency {
     }
 }
 
+impl PartialEq for Currency {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
 impl Eq for Currency {}
 
+impl Hash for Currency {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value().hash(state)
+    }
+}
+
+/// Serde form of `Currency`, built-in ids are always written as their named variants
+#[cfg(feature = "std")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum CurrencyRepr {
+    Unknown,
+    Usd,
+    Eq,
+    Eth,
+    Btc,
+    Eos,
+    Other(u8),
+}
+
+#[cfg(feature = "std")]
+impl From<CurrencyRepr> for Currency {
+    fn from(repr: CurrencyRepr) -> Self {
+        match repr {
+            CurrencyRepr::Unknown => Currency::Unknown,
+            CurrencyRepr::Usd => Currency::Usd,
+            CurrencyRepr::Eq => Currency::Eq,
+            CurrencyRepr::Eth => Currency::Eth,
+            CurrencyRepr::Btc => Currency::Btc,
+            CurrencyRepr::Eos => Currency::Eos,
+            CurrencyRepr::Other(id) => Currency::from_id(id),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Currency> for CurrencyRepr {
+    fn from(currency: Currency) -> Self {
+        match Currency::from_id(currency.value()) {
+            Currency::Unknown => CurrencyRepr::Unknown,
+            Currency::Usd => CurrencyRepr::Usd,
+            Currency::Eq => CurrencyRepr::Eq,
+            Currency::Eth => CurrencyRepr::Eth,
+            Currency::Btc => CurrencyRepr::Btc,
+            Currency::Eos => CurrencyRepr::Eos,
+            Currency::Other(id) => CurrencyRepr::Other(id),
+        }
+    }
+}
+
 impl Default for Currency {
     fn default() -> Currency {
         Currency::Unknown
     }
 }
 
+/// Decimals of currencies without their own metadata
+pub const DEFAULT_DECIMALS: u8 = 9;
+
 impl Currency {
+    /// Number of decimals of the currency amounts. Currencies without a built-in variant
+    /// use `DEFAULT_DECIMALS` unless the asset registry says otherwise.
+    pub fn decimals(&self) -> u8 {
+        match Currency::from_id(self.value()) {
+            Currency::Usd | Currency::Eq | Currency::Eth | Currency::Btc | Currency::Eos => 9,
+            Currency::Unknown | Currency::Other(_) => DEFAULT_DECIMALS,
+        }
+    }
+
+    /// Ticker of the built-in currency
+    pub fn ticker(&self) -> Option<&'static str> {
+        match Currency::from_id(self.value()) {
+            Currency::Unknown => Some("UNKNOWN"),
+            Currency::Usd => Some("USD"),
+            Currency::Eq => Some("EQ"),
+            Currency::Eth => Some("ETH"),
+            Currency::Btc => Some("BTC"),
+            Currency::Eos => Some("EOS"),
+            Currency::Other(_) => None,
+        }
+    }
+
     pub fn value(&self) -> u8 {
         match *self {
             Currency::Unknown => 0x0,
@@ -54,14 +146,25 @@ impl Currency {
             Currency::Eth => 0x3,
             Currency::Btc => 0x4,
             Currency::Eos => 0x5,
+            Currency::Other(id) => id,
+        }
+    }
+
+    /// Currency of the id, built-in ids map to their named variants and the rest to
+    /// `Other`
+    pub fn from_id(id: u8) -> Self {
+        match Currency::from(id) {
+            Currency::Unknown if id != Currency::Unknown.value() => Currency::Other(id),
+            currency => currency,
         }
     }
 }
 
+/// Built-in currency of the id, ids without a named variant are `Unknown`. Currencies
+/// of the asset registry are read with `Currency::from_id`.
 impl From<u8> for Currency {
     fn from(orig: u8) -> Self {
         match orig {
-            0x0 => Currency::Unknown,
             0x1 => Currency::Usd,
             0x2 => Currency::Eq,
             0x3 => Currency::Eth,
@@ -72,6 +175,65 @@ impl From<u8> for Currency {
     }
 }
 
+/// Ticker of the currency, `#<id>` for currencies without a built-in variant
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.ticker() {
+            Some(ticker) => f.write_str(ticker),
+            None => write!(f, "#{}", self.value()),
+        }
+    }
+}
+
+/// Error of parsing a currency ticker
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct ParseCurrencyError;
+
+impl fmt::Display for ParseCurrencyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown currency ticker")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseCurrencyError {}
+
+/// Parses tickers printed by `Display`, case insensitive
+impl FromStr for Currency {
+    type Err = ParseCurrencyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(id) = s.strip_prefix('#') {
+            return id
+                .parse::<u8>()
+                .map(Currency::from_id)
+                .map_err(|_| ParseCurrencyError);
+        }
+        (0..=Currency::Eos.value())
+            .map(Currency::from)
+            .find(|c| c.ticker().map_or(false, |t| t.eq_ignore_ascii_case(s)))
+            .ok_or(ParseCurrencyError)
+    }
+}
+
+impl Encode for Currency {
+    fn size_hint(&self) -> usize {
+        1
+    }
+
+    fn encode_to<W: Output>(&self, dest: &mut W) {
+        dest.push_byte(self.value())
+    }
+}
+
+impl EncodeLike for Currency {}
+
+impl Decode for Currency {
+    fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+        Ok(Currency::from_id(input.read_byte()?))
+    }
+}
+
 pub mod test {
     use crate::currency::Currency;
     use sp_std::cmp::Ordering;
@@ -85,7 +247,7 @@ pub mod test {
             CurrencyTag { currency }
         }
         fn value(&self) -> i32 {
-            match self.currency {
+            match Currency::from_id(self.currency.value()) {
                 Currency::Btc => 0,
                 Currency::Eth => 1,
                 Currency::Eos => 2,
@@ -112,3 +274,59 @@ pub mod test {
         }
     }
 }
+
+mod tests {
+    #![cfg(test)]
+
+    use super::*;
+
+    #[test]
+    fn built_in_ids_decode_to_named_variants() {
+        assert_eq!(Currency::decode(&mut &[0x4][..]).ok(), Some(Currency::Btc));
+        assert_eq!(
+            Currency::decode(&mut &[100][..]).ok(),
+            Some(Currency::Other(100))
+        );
+        assert_eq!(
+            Currency::decode(&mut &Currency::Other(1).encode()[..]).ok(),
+            Some(Currency::Usd)
+        );
+        assert_eq!(Currency::Other(1), Currency::Usd);
+    }
+
+    #[test]
+    fn currencies_are_compared_and_hashed_by_id() {
+        use std::collections::HashSet;
+
+        let currencies: HashSet<_> = vec![Currency::Other(4), Currency::Btc, Currency::Other(0)]
+            .into_iter()
+            .collect();
+        assert_eq!(currencies.len(), 2);
+        assert!(currencies.contains(&Currency::Btc));
+        assert!(currencies.contains(&Currency::Unknown));
+        assert_ne!(Currency::Other(100), Currency::Other(101));
+    }
+
+    #[test]
+    fn serde_uses_named_variants_of_built_in_ids() {
+        let btc: Currency = serde_json::from_str(r#"{"other":4}"#).unwrap();
+        assert!(matches!(btc, Currency::Btc));
+        assert_eq!(
+            serde_json::to_string(&Currency::Other(4)).unwrap(),
+            r#""btc""#
+        );
+        assert_eq!(serde_json::to_string(&Currency::Eth).unwrap(), r#""eth""#);
+
+        let other: Currency = serde_json::from_str(r#"{"other":100}"#).unwrap();
+        assert!(matches!(other, Currency::Other(100)));
+        assert_eq!(serde_json::to_string(&other).unwrap(), r#"{"other":100}"#);
+    }
+
+    #[test]
+    fn from_u8_maps_unknown_ids_to_unknown() {
+        assert_eq!(Currency::from(0x3), Currency::Eth);
+        assert_eq!(Currency::from(100), Currency::Unknown);
+        assert_eq!(Currency::from_id(0), Currency::Unknown);
+        assert_eq!(Currency::from_id(100), Currency::Other(100));
+    }
+}
@@ -1,9 +1,13 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use eq_utils::math::MathError;
 use impl_trait_for_tuples::impl_for_tuples;
 use sp_runtime::traits::AtLeast32Bit;
 use sp_std::prelude::*;
 
+#[cfg(feature = "std")]
+pub mod amount;
+pub mod asset;
 pub mod currency;
 
 #[derive(PartialEq, Debug, Clone)]
@@ -13,44 +17,249 @@ pub enum InterestRateError {
     ValueError,
 }
 
-pub trait FeeManager<AccountId, Balance> {
-    fn calc_fee(owner: &AccountId, last_update: &u64) -> Result<Balance, InterestRateError>;
-    fn charge_fee_inner(owner: &AccountId, debt: &Balance);
-    fn charge_fee(owner: &AccountId, last_update: &u64) -> Result<Balance, InterestRateError> {
-        let debt_delta = Self::calc_fee(owner, last_update)?;
-        Self::charge_fee_inner(owner, &debt_delta);
-        Ok(debt_delta)
+impl From<MathError> for InterestRateError {
+    fn from(_: MathError) -> Self {
+        InterestRateError::MathError
     }
 }
 
-#[impl_for_tuples(5)]
-impl<AccountId, Balance: AtLeast32Bit + Clone> FeeManager<AccountId, Balance> for Tuple {
-    fn calc_fee(owner: &AccountId, last_update: &u64) -> Result<Balance, InterestRateError> {
-        let mut debt = Balance::zero();
-        for_tuples!( #( debt = debt + Tuple::calc_fee(owner, last_update)?; )* );
-        Ok(debt)
+/// Component of the fees charged by a `FeeManager`
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum FeeKind {
+    Interest,
+    BailsmanFee,
+    TreasuryFee,
+}
+
+/// Fee calculated by a single component
+#[derive(PartialEq, Debug, Clone)]
+pub struct ComponentFee<Balance> {
+    pub kind: FeeKind,
+    pub fee: Result<Balance, InterestRateError>,
+}
+
+/// Fees of all components, in the order of the components
+#[derive(PartialEq, Debug, Clone)]
+pub struct FeeReport<Balance> {
+    pub components: Vec<ComponentFee<Balance>>,
+}
+
+impl<Balance> Default for FeeReport<Balance> {
+    fn default() -> Self {
+        FeeReport { components: vec![] }
     }
-    fn charge_fee_inner(owner: &AccountId, debt: &Balance) {
-        panic!("Don't use this in tuple!");
+}
+
+impl<Balance: AtLeast32Bit + Clone> FeeReport<Balance> {
+    /// Report of a single component
+    pub fn single(kind: FeeKind, fee: Result<Balance, InterestRateError>) -> Self {
+        FeeReport {
+            components: vec![ComponentFee { kind, fee }],
+        }
+    }
+
+    /// Fee of the first component of the kind
+    pub fn fee_of(&self, kind: FeeKind) -> Option<&Result<Balance, InterestRateError>> {
+        self.components
+            .iter()
+            .find(|c| c.kind == kind)
+            .map(|c| &c.fee)
     }
-    fn charge_fee(owner: &AccountId, last_update: &u64) -> Result<Balance, InterestRateError> {
-        let mut debt = Balance::zero();
-        let mut fees = Vec::<Balance>::new();
-        for_tuples!( #( {
-            let fee = Tuple::calc_fee(owner, last_update)?;
-            debt = debt + fee.clone();
-            fees.push(fee);
-        } )* );
 
-        let mut index: usize = 0;
+    /// Sum of all fees, the first error of a component otherwise
+    pub fn total(&self) -> Result<Balance, InterestRateError> {
+        self.components
+            .iter()
+            .try_fold(Balance::zero(), |total, c| {
+                let fee = c.fee.clone()?;
+                total.checked_add(&fee).ok_or(InterestRateError::MathError)
+            })
+    }
+}
+
+pub trait FeeManager<AccountId, Balance: AtLeast32Bit + Clone> {
+    /// Fees of every component, an error of one component doesn't hide the others
+    fn calc_fee(owner: &AccountId, last_update: &u64) -> FeeReport<Balance>;
+    /// Charges the leading fees that this manager reported in `calc_fee`, returns the
+    /// fees of the following components
+    fn charge_fee_inner<'a>(
+        owner: &AccountId,
+        fees: &'a [ComponentFee<Balance>],
+    ) -> &'a [ComponentFee<Balance>];
+    /// Calculates and charges the fees. Nothing is charged unless every component
+    /// succeeded, see `FeeReport::total`.
+    fn charge_fee(owner: &AccountId, last_update: &u64) -> FeeReport<Balance> {
+        let report = Self::calc_fee(owner, last_update);
+        if report.total().is_ok() {
+            Self::charge_fee_inner(owner, &report.components);
+        }
+        report
+    }
+}
+
+/// Components report and charge their fees in the order of the tuple
+#[impl_for_tuples(5)]
+impl<AccountId, Balance: AtLeast32Bit + Clone> FeeManager<AccountId, Balance> for Tuple {
+    fn calc_fee(owner: &AccountId, last_update: &u64) -> FeeReport<Balance> {
+        let mut report = FeeReport::default();
         for_tuples!( #( {
-            let debt_delta = Tuple::charge_fee_inner(owner, &fees[index]);
-            index += 1;
+            let part = Tuple::calc_fee(owner, last_update);
+            report.components.extend(part.components);
         } )* );
-        Ok(debt)
+        report
+    }
+    fn charge_fee_inner<'a>(
+        owner: &AccountId,
+        fees: &'a [ComponentFee<Balance>],
+    ) -> &'a [ComponentFee<Balance>] {
+        let mut rest = fees;
+        for_tuples!( #( rest = Tuple::charge_fee_inner(owner, rest); )* );
+        rest
     }
 }
 
 pub trait AccountGetter<AccountId> {
     fn get_account_id() -> AccountId;
 }
+
+mod test {
+    #![cfg(test)]
+
+    use super::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static CHARGED: RefCell<Vec<(u64, FeeKind, u64)>> = RefCell::new(vec![]);
+    }
+
+    fn charged() -> Vec<(u64, FeeKind, u64)> {
+        CHARGED.with(|c| c.borrow().clone())
+    }
+
+    macro_rules! component {
+        ($name:ident, $kind:expr, $fee:expr) => {
+            struct $name;
+            impl FeeManager<u64, u64> for $name {
+                fn calc_fee(_owner: &u64, last_update: &u64) -> FeeReport<u64> {
+                    let fee: fn(u64) -> Result<u64, InterestRateError> = $fee;
+                    FeeReport::single($kind, fee(*last_update))
+                }
+                fn charge_fee_inner<'a>(
+                    owner: &u64,
+                    fees: &'a [ComponentFee<u64>],
+                ) -> &'a [ComponentFee<u64>] {
+                    if let Some(ComponentFee { fee: Ok(fee), .. }) = fees.first() {
+                        CHARGED.with(|c| c.borrow_mut().push((*owner, $kind, *fee)));
+                    }
+                    fees.get(1..).unwrap_or(&[])
+                }
+            }
+        };
+    }
+
+    component!(Interest, FeeKind::Interest, |t| Ok(t * 10));
+    component!(Bailsman, FeeKind::BailsmanFee, |t| Ok(t));
+    component!(Treasury, FeeKind::TreasuryFee, |_| Ok(5));
+    component!(BrokenTreasury, FeeKind::TreasuryFee, |_| Err(
+        InterestRateError::ExternalError
+    ));
+    component!(Penalty, FeeKind::TreasuryFee, |t| Ok(t + 1));
+    component!(Huge, FeeKind::TreasuryFee, |_| Ok(u64::max_value()));
+
+    fn fee(kind: FeeKind, fee: Result<u64, InterestRateError>) -> ComponentFee<u64> {
+        ComponentFee { kind, fee }
+    }
+
+    #[test]
+    fn report_per_component() {
+        let report = <(Interest, Bailsman, Treasury)>::charge_fee(&1, &3);
+        assert_eq!(
+            report.components,
+            vec![
+                fee(FeeKind::Interest, Ok(30)),
+                fee(FeeKind::BailsmanFee, Ok(3)),
+                fee(FeeKind::TreasuryFee, Ok(5)),
+            ]
+        );
+        assert_eq!(report.total(), Ok(38));
+        assert_eq!(report.fee_of(FeeKind::BailsmanFee), Some(&Ok(3)));
+        assert_eq!(
+            charged(),
+            vec![
+                (1, FeeKind::Interest, 30),
+                (1, FeeKind::BailsmanFee, 3),
+                (1, FeeKind::TreasuryFee, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_component_charges_nothing() {
+        let report = <(Interest, BrokenTreasury, Bailsman)>::charge_fee(&1, &3);
+        assert_eq!(
+            report.components,
+            vec![
+                fee(FeeKind::Interest, Ok(30)),
+                fee(FeeKind::TreasuryFee, Err(InterestRateError::ExternalError)),
+                fee(FeeKind::BailsmanFee, Ok(3)),
+            ]
+        );
+        assert_eq!(report.total(), Err(InterestRateError::ExternalError));
+        assert_eq!(charged(), vec![]);
+    }
+
+    #[test]
+    fn total_overflow_charges_nothing() {
+        let report = <(Interest, Huge)>::charge_fee(&1, &3);
+        assert_eq!(report.total(), Err(InterestRateError::MathError));
+        assert_eq!(charged(), vec![]);
+    }
+
+    #[test]
+    fn nested_and_empty_tuples() {
+        let report = <(Interest, ((), (Bailsman, Treasury)))>::calc_fee(&2, &1);
+        assert_eq!(report.total(), Ok(16));
+
+        // every entry point of the combinator is safe to call directly
+        let rest =
+            <(Interest, ((), (Bailsman, Treasury)))>::charge_fee_inner(&2, &report.components);
+        assert!(rest.is_empty());
+        assert_eq!(
+            charged(),
+            vec![
+                (2, FeeKind::Interest, 10),
+                (2, FeeKind::BailsmanFee, 1),
+                (2, FeeKind::TreasuryFee, 5),
+            ]
+        );
+
+        let empty = <()>::charge_fee(&2, &1);
+        assert_eq!(empty, FeeReport::default());
+        assert_eq!(empty.total(), Ok(0));
+    }
+
+    #[test]
+    fn same_kind_components_charge_own_fees() {
+        let report = <(Treasury, Bailsman, Penalty)>::charge_fee(&1, &3);
+        assert_eq!(report.fee_of(FeeKind::TreasuryFee), Some(&Ok(5)));
+        assert_eq!(
+            charged(),
+            vec![
+                (1, FeeKind::TreasuryFee, 5),
+                (1, FeeKind::BailsmanFee, 3),
+                (1, FeeKind::TreasuryFee, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn math_error_is_interest_rate_error() {
+        use eq_utils::math::MathUtils;
+        use sp_runtime::{FixedI64, FixedPointNumber};
+
+        let rate = || -> Result<FixedI64, InterestRateError> {
+            Ok(FixedI64::saturating_from_integer(-1).ln()?)
+        };
+        assert_eq!(rate(), Err(InterestRateError::MathError));
+    }
+}
@@ -5,4 +5,7 @@ use sp_arithmetic::*;
 
 pub mod log;
 pub mod math;
+pub mod matrix;
+pub mod normal;
+pub mod statistics;
 pub mod test;
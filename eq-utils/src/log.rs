@@ -1,17 +1,126 @@
-pub use crate::eq_log;
+//! Levelled logging through the `log` facade. Native builds always log, the wasm
+//! runtime compiles the calls out unless the `runtime-logging` feature is enabled, the
+//! runtime then installs `frame_support::debug::RuntimeLogger` on every entry point.
+//!
+//! Every macro takes an optional `target:`, by default it's the caller's module path.
+
+pub use crate::{eq_debug, eq_error, eq_info, eq_trace, eq_warn};
+
+#[doc(hidden)]
+pub use ::log as facade;
+
+#[cfg(any(feature = "std", feature = "runtime-logging"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __eq_log {
+    (target: $target:expr, $level:ident, $($arg:tt)+) => {
+        $crate::log::facade::log!(
+            target: $target,
+            $crate::log::facade::Level::$level,
+            $($arg)+
+        )
+    };
+    ($level:ident, $($arg:tt)+) => {
+        $crate::log::facade::log!($crate::log::facade::Level::$level, $($arg)+)
+    };
+}
+
+#[cfg(not(any(feature = "std", feature = "runtime-logging")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __eq_log {
+    (target: $target:expr, $level:ident, $($arg:tt)+) => {{
+        let _ = $target;
+        $crate::__eq_log!($level, $($arg)+)
+    }};
+    ($level:ident, $($arg:tt)+) => {{
+        // arguments are type checked and not evaluated
+        if false {
+            let _ = ::core::format_args!($($arg)+);
+        }
+    }};
+}
 
-#[cfg(feature = "std")]
 #[macro_export]
-macro_rules! eq_log {
-  ($($arg:tt)+) => {
-    println!($($arg)+);
-  }
+macro_rules! eq_trace {
+    (target: $target:expr, $($arg:tt)+) => { $crate::__eq_log!(target: $target, Trace, $($arg)+) };
+    ($($arg:tt)+) => { $crate::__eq_log!(Trace, $($arg)+) };
 }
 
-#[cfg(not(feature = "std"))]
 #[macro_export]
-macro_rules! eq_log {
-  ($($arg:tt)+) => {
-        debug::warn!($($arg)+)
+macro_rules! eq_debug {
+    (target: $target:expr, $($arg:tt)+) => { $crate::__eq_log!(target: $target, Debug, $($arg)+) };
+    ($($arg:tt)+) => { $crate::__eq_log!(Debug, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! eq_info {
+    (target: $target:expr, $($arg:tt)+) => { $crate::__eq_log!(target: $target, Info, $($arg)+) };
+    ($($arg:tt)+) => { $crate::__eq_log!(Info, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! eq_warn {
+    (target: $target:expr, $($arg:tt)+) => { $crate::__eq_log!(target: $target, Warn, $($arg)+) };
+    ($($arg:tt)+) => { $crate::__eq_log!(Warn, $($arg)+) };
+}
+
+#[macro_export]
+macro_rules! eq_error {
+    (target: $target:expr, $($arg:tt)+) => { $crate::__eq_log!(target: $target, Error, $($arg)+) };
+    ($($arg:tt)+) => { $crate::__eq_log!(Error, $($arg)+) };
+}
+
+mod test {
+    #![cfg(test)]
+
+    use super::facade::{Level, LevelFilter, Log, Metadata, Record};
+    use std::cell::RefCell;
+
+    thread_local! {
+        static RECORDS: RefCell<Vec<(Level, String, String)>> = RefCell::new(vec![]);
+    }
+
+    struct TestLogger;
+    impl Log for TestLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS.with(|records| {
+                records.borrow_mut().push((
+                    record.level(),
+                    record.target().to_string(),
+                    record.args().to_string(),
+                ))
+            });
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn levels_and_targets() {
+        super::facade::set_logger(&TestLogger).unwrap();
+        super::facade::set_max_level(LevelFilter::Trace);
+
+        let who = 42;
+        eq_trace!(target: "eq_test", "trace {}", who);
+        eq_debug!(target: "eq_test", "debug {:?}", Some(who));
+        eq_info!(target: "eq_test", "info");
+        eq_warn!("warn {}", who);
+        eq_error!(target: "eq_test", "error {who}", who = who);
+
+        assert_eq!(
+            RECORDS.with(|records| records.borrow().clone()),
+            vec![
+                (Level::Trace, "eq_test".into(), "trace 42".into()),
+                (Level::Debug, "eq_test".into(), "debug Some(42)".into()),
+                (Level::Info, "eq_test".into(), "info".into()),
+                (Level::Warn, module_path!().into(), "warn 42".into()),
+                (Level::Error, "eq_test".into(), "error 42".into()),
+            ]
+        );
     }
 }
@@ -1,60 +1,109 @@
 use super::*;
+use sp_std::convert::TryFrom;
 use substrate_fixed;
 use substrate_fixed::transcendental;
 
-type InnerFixed = substrate_fixed::FixedI128<substrate_fixed::types::extra::U64>;
+pub(crate) type InnerFixed = substrate_fixed::FixedI128<substrate_fixed::types::extra::U64>;
 
-fn to_inner_fixed(x: FixedI64) -> InnerFixed {
+pub(crate) fn to_inner_fixed(x: FixedI64) -> InnerFixed {
     let nom = InnerFixed::from_num(x.into_inner());
     let denom = InnerFixed::from_num(FixedI64::DIV);
 
     nom / denom
 }
 
-fn from_inner_fixed(x: InnerFixed) -> FixedI64 {
-    let y = x * InnerFixed::from_num(FixedI64::DIV);
-    let raw = y.round().to_bits() >> 64;
+/// `None` when the value doesn't fit into `FixedI64`
+pub(crate) fn checked_from_inner_fixed(x: InnerFixed) -> Option<FixedI64> {
+    let y = x.checked_mul(InnerFixed::from_num(FixedI64::DIV))?;
+    let raw = y.checked_round()?.to_bits() >> 64;
 
-    FixedI64::from_inner(raw as i64)
+    TryFrom::try_from(raw).ok().map(FixedI64::from_inner)
+}
+
+/// Like `checked_from_inner_fixed`, also fails when a nonzero value rounds to zero
+fn from_inner_fixed(x: InnerFixed) -> Result<FixedI64, MathError> {
+    let result = checked_from_inner_fixed(x).ok_or(MathError::Overflow)?;
+    if result.into_inner() == 0 && x != InnerFixed::from_num(0) {
+        return Err(MathError::PrecisionLoss);
+    }
+    Ok(result)
+}
+
+/// Error of a `MathUtils` function
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MathError {
+    /// Argument is outside of the function domain, e.g. `ln` of a negative number
+    OutOfDomain,
+    /// Result or intermediate value doesn't fit into the fixed point range
+    Overflow,
+    /// Nonzero result is too small to be represented and would round to zero
+    PrecisionLoss,
 }
 
 pub trait MathUtils
 where
     Self: Sized,
 {
-    fn sqrt(self) -> Result<Self, ()>;
-    fn ln(self) -> Result<Self, ()>;
+    fn sqrt(self) -> Result<Self, MathError>;
+    fn ln(self) -> Result<Self, MathError>;
 
-    fn sqr(self) -> Self; // add Option or result?
+    /// Saturating square, see `checked_sqr`
+    fn sqr(self) -> Self;
+    fn checked_sqr(self) -> Result<Self, MathError>;
 
-    fn exp(self) -> Result<Self, ()>;
+    fn exp(self) -> Result<Self, MathError>;
 
-    fn pow(self, y: Self) -> Result<Self, ()>;
+    /// Power of a non-negative base, zero base requires a non-negative exponent
+    fn pow(self, y: Self) -> Result<Self, MathError>;
 }
 
 impl MathUtils for FixedI64 {
-    fn sqrt(self) -> Result<Self, ()> {
-        let result = transcendental::sqrt(to_inner_fixed(self))?;
-        Ok(from_inner_fixed(result))
+    fn sqrt(self) -> Result<Self, MathError> {
+        if self.into_inner() < 0 {
+            return Err(MathError::OutOfDomain);
+        }
+        if self.into_inner() == 0 {
+            return Ok(self);
+        }
+        let result = transcendental::sqrt(to_inner_fixed(self)).map_err(|_| MathError::Overflow)?;
+        from_inner_fixed(result)
     }
 
     fn sqr(self) -> Self {
         self.saturating_mul(self)
     }
 
-    fn ln(self) -> Result<Self, ()> {
-        let result = transcendental::ln(to_inner_fixed(self))?;
-        Ok(from_inner_fixed(result))
+    fn checked_sqr(self) -> Result<Self, MathError> {
+        let x = to_inner_fixed(self);
+        from_inner_fixed(x.checked_mul(x).ok_or(MathError::Overflow)?)
+    }
+
+    fn ln(self) -> Result<Self, MathError> {
+        if self.into_inner() <= 0 {
+            return Err(MathError::OutOfDomain);
+        }
+        let result = transcendental::ln(to_inner_fixed(self)).map_err(|_| MathError::Overflow)?;
+        from_inner_fixed(result)
     }
 
-    fn exp(self) -> Result<Self, ()> {
-        let result = transcendental::exp(to_inner_fixed(self))?;
-        Ok(from_inner_fixed(result))
+    fn exp(self) -> Result<Self, MathError> {
+        let result = transcendental::exp(to_inner_fixed(self)).map_err(|_| MathError::Overflow)?;
+        from_inner_fixed(result)
     }
 
-    fn pow(self, y: Self) -> Result<Self, ()> {
-        let result = transcendental::pow(to_inner_fixed(self), to_inner_fixed(y))?;
-        Ok(from_inner_fixed(result))
+    fn pow(self, y: Self) -> Result<Self, MathError> {
+        if self.into_inner() < 0 || (self.into_inner() == 0 && y.into_inner() < 0) {
+            return Err(MathError::OutOfDomain);
+        }
+        if y.into_inner() == 0 {
+            return Ok(Self::saturating_from_integer(1));
+        }
+        if self.into_inner() == 0 {
+            return Ok(self);
+        }
+        let result = transcendental::pow(to_inner_fixed(self), to_inner_fixed(y))
+            .map_err(|_| MathError::Overflow)?;
+        from_inner_fixed(result)
     }
 }
 
@@ -131,12 +180,64 @@ fn ln_test() {
 
 #[test]
 fn ln_fails() {
-    assert!(FixedI64::saturating_from_integer(-1).ln().is_err());
+    assert_eq!(
+        FixedI64::saturating_from_integer(-1).ln(),
+        Err(MathError::OutOfDomain)
+    );
+    assert_eq!(fx64!(0, 0).ln(), Err(MathError::OutOfDomain));
 }
 
 #[test]
 fn sqrt_fails() {
-    assert!(FixedI64::saturating_from_integer(-1).sqrt().is_err());
+    assert_eq!(
+        FixedI64::saturating_from_integer(-1).sqrt(),
+        Err(MathError::OutOfDomain)
+    );
+}
+
+#[test]
+fn sqrt_of_zero() {
+    assert_eq!(fx64!(0, 0).sqrt(), Ok(fx64!(0, 0)));
+}
+
+#[test]
+fn sqr_test() {
+    assert_eq!(fx64!(1, 5).checked_sqr(), Ok(fx64!(2, 25)));
+    assert_eq!(fx64!(-3, 0).checked_sqr(), Ok(fx64!(9, 0)));
+    assert_eq!(fx64!(-3, 0).sqr(), fx64!(9, 0));
+}
+
+#[test]
+fn sqr_fails() {
+    let max = FixedI64::from_inner(i64::max_value());
+
+    assert_eq!(max.checked_sqr(), Err(MathError::Overflow));
+    assert_eq!(max.sqr(), max);
+    assert_eq!(fx64!(0, 00001).checked_sqr(), Err(MathError::PrecisionLoss));
+    assert_eq!(fx64!(0, 00001).sqr(), fx64!(0, 0));
+}
+
+#[test]
+fn exp_fails() {
+    assert_eq!(fx64!(30, 0).exp(), Err(MathError::Overflow));
+    assert_eq!(fx64!(-30, 0).exp(), Err(MathError::PrecisionLoss));
+}
+
+#[test]
+fn pow_fails() {
+    assert_eq!(fx64!(-2, 0).pow(fx64!(2, 0)), Err(MathError::OutOfDomain));
+    assert_eq!(fx64!(0, 0).pow(fx64!(-1, 0)), Err(MathError::OutOfDomain));
+    assert_eq!(fx64!(10, 0).pow(fx64!(12, 0)), Err(MathError::Overflow));
+    assert_eq!(
+        fx64!(10, 0).pow(fx64!(-12, 0)),
+        Err(MathError::PrecisionLoss)
+    );
+}
+
+#[test]
+fn pow_of_zero() {
+    assert_eq!(fx64!(0, 0).pow(fx64!(2, 0)), Ok(fx64!(0, 0)));
+    assert_eq!(fx64!(0, 0).pow(fx64!(0, 0)), Ok(fx64!(1, 0)));
 }
 
 #[test]
@@ -206,5 +307,5 @@ fn inner_fixed_conversions() {
     let inner_fixed = to_inner_fixed(num);
     let actual = from_inner_fixed(inner_fixed);
 
-    assert_eq!(actual, num);
+    assert_eq!(actual, Ok(num));
 }
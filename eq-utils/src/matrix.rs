@@ -0,0 +1,440 @@
+//! Dense matrices over `FixedI64` for portfolio risk. Sums of products are accumulated
+//! with 64 fractional bits and rounded once into each element of the result.
+
+use super::*;
+use crate::math::{checked_from_inner_fixed, to_inner_fixed, InnerFixed};
+use sp_std::prelude::*;
+use substrate_fixed::transcendental;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MatrixError {
+    /// Operand dimensions don't agree or data doesn't fill the matrix
+    DimensionMismatch,
+    /// Covariance map has no entry for a pair of keys
+    MissingCovariance,
+    /// Matrix must be square and symmetric
+    NotSymmetric,
+    /// Cholesky decomposition requires a positive definite matrix
+    NotPositiveDefinite,
+    /// Intermediate value or result doesn't fit into the fixed point range
+    Overflow,
+}
+
+/// Row-major matrix of `FixedI64`
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<FixedI64>,
+}
+
+impl Matrix {
+    /// Matrix from row-major `data` of `rows * cols` elements
+    pub fn new(rows: usize, cols: usize, data: Vec<FixedI64>) -> Result<Self, MatrixError> {
+        if rows.checked_mul(cols) != Some(data.len()) {
+            return Err(MatrixError::DimensionMismatch);
+        }
+        Ok(Matrix { rows, cols, data })
+    }
+
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![FixedI64::from_inner(0); rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zero(n, n);
+        for i in 0..n {
+            m.data[i * n + i] = FixedI64::from_inner(FixedI64::DIV);
+        }
+        m
+    }
+
+    /// Covariance matrix of `keys` in the given order. Every pair must be present in
+    /// `covariances` in either order, the entry of `(a, b)` is preferred to `(b, a)`.
+    pub fn from_covariances<K: PartialEq>(
+        keys: &[K],
+        covariances: &[(K, K, FixedI64)],
+    ) -> Result<Self, MatrixError> {
+        let find = |a: &K, b: &K| {
+            covariances
+                .iter()
+                .find(|(x, y, _)| x == a && y == b)
+                .map(|(_, _, c)| *c)
+        };
+        let n = keys.len();
+        let mut data = Vec::with_capacity(n * n);
+        for a in keys {
+            for b in keys {
+                let c = find(a, b)
+                    .or_else(|| find(b, a))
+                    .ok_or(MatrixError::MissingCovariance)?;
+                data.push(c);
+            }
+        }
+        Self::new(n, n, data)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Element at `row` and `col`, `None` outside of the matrix
+    pub fn get(&self, row: usize, col: usize) -> Option<FixedI64> {
+        if row < self.rows && col < self.cols {
+            Some(self.data[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut data = Vec::with_capacity(self.data.len());
+        for col in 0..self.cols {
+            for row in 0..self.rows {
+                data.push(self.at(row, col));
+            }
+        }
+        Matrix {
+            rows: self.cols,
+            cols: self.rows,
+            data,
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        self.rows == self.cols
+            && (0..self.rows).all(|i| (0..i).all(|j| self.at(i, j) == self.at(j, i)))
+    }
+
+    /// Matrix product `self * other`
+    pub fn checked_mul(&self, other: &Matrix) -> Result<Matrix, MatrixError> {
+        if self.cols != other.rows {
+            return Err(MatrixError::DimensionMismatch);
+        }
+        let mut data = Vec::with_capacity(self.rows * other.cols);
+        for row in 0..self.rows {
+            for col in 0..other.cols {
+                let sum = (0..self.cols).try_fold(InnerFixed::from_num(0), |sum, k| {
+                    mul_add(sum, self.at(row, k), other.at(k, col))
+                })?;
+                data.push(from_inner(sum)?);
+            }
+        }
+        Matrix::new(self.rows, other.cols, data)
+    }
+
+    /// Matrix-vector product `self * v`
+    pub fn checked_mul_vec(&self, v: &[FixedI64]) -> Result<Vec<FixedI64>, MatrixError> {
+        if self.cols != v.len() {
+            return Err(MatrixError::DimensionMismatch);
+        }
+        (0..self.rows)
+            .map(|row| {
+                let sum = v
+                    .iter()
+                    .enumerate()
+                    .try_fold(InnerFixed::from_num(0), |sum, (k, x)| {
+                        mul_add(sum, self.at(row, k), *x)
+                    })?;
+                from_inner(sum)
+            })
+            .collect()
+    }
+
+    /// Quadratic form `wᵀ * self * w`, variance of the portfolio with weights `w` when
+    /// `self` is the covariance matrix
+    pub fn quadratic_form(&self, w: &[FixedI64]) -> Result<FixedI64, MatrixError> {
+        if self.rows != self.cols || self.cols != w.len() {
+            return Err(MatrixError::DimensionMismatch);
+        }
+        let mut sum = InnerFixed::from_num(0);
+        for (i, wi) in w.iter().enumerate() {
+            for (j, wj) in w.iter().enumerate() {
+                let term = checked(to_inner_fixed(*wi).checked_mul(to_inner_fixed(self.at(i, j))))?;
+                sum = checked(sum.checked_add(checked(term.checked_mul(to_inner_fixed(*wj)))?))?;
+            }
+        }
+        from_inner(sum)
+    }
+
+    /// Whether the symmetric matrix is positive semidefinite. Runs the `LDLᵀ`
+    /// decomposition, pivots within one `FixedI64` unit of zero are treated as zero.
+    pub fn is_positive_semidefinite(&self) -> Result<bool, MatrixError> {
+        if !self.is_symmetric() {
+            return Err(MatrixError::NotSymmetric);
+        }
+        let n = self.rows;
+        let eps = InnerFixed::from_num(1) / InnerFixed::from_num(FixedI64::DIV);
+        let mut l = vec![InnerFixed::from_num(0); n * n];
+        let mut d = vec![InnerFixed::from_num(0); n];
+
+        for j in 0..n {
+            let mut dj = to_inner_fixed(self.at(j, j));
+            for k in 0..j {
+                let ljk = l[j * n + k];
+                dj = checked(
+                    dj.checked_sub(checked(checked(ljk.checked_mul(ljk))?.checked_mul(d[k]))?),
+                )?;
+            }
+            if dj < -eps {
+                return Ok(false);
+            }
+            let zero_pivot = dj <= eps;
+            d[j] = if zero_pivot {
+                InnerFixed::from_num(0)
+            } else {
+                dj
+            };
+
+            for i in (j + 1)..n {
+                let mut lij = to_inner_fixed(self.at(i, j));
+                for k in 0..j {
+                    let term = checked(
+                        checked(l[i * n + k].checked_mul(l[j * n + k]))?.checked_mul(d[k]),
+                    )?;
+                    lij = checked(lij.checked_sub(term))?;
+                }
+                if zero_pivot {
+                    // zero variance direction must not correlate with anything
+                    if lij.abs() > eps {
+                        return Ok(false);
+                    }
+                } else {
+                    l[i * n + j] = checked(lij.checked_div(dj))?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Lower triangular `L` with `L * Lᵀ = self` for a symmetric positive definite matrix
+    pub fn cholesky(&self) -> Result<Matrix, MatrixError> {
+        if !self.is_symmetric() {
+            return Err(MatrixError::NotSymmetric);
+        }
+        let n = self.rows;
+        let mut l = vec![InnerFixed::from_num(0); n * n];
+
+        for j in 0..n {
+            let mut diag = to_inner_fixed(self.at(j, j));
+            for k in 0..j {
+                diag = checked(diag.checked_sub(checked(l[j * n + k].checked_mul(l[j * n + k]))?))?;
+            }
+            if diag <= InnerFixed::from_num(0) {
+                return Err(MatrixError::NotPositiveDefinite);
+            }
+            let ljj: InnerFixed = transcendental::sqrt(diag).map_err(|_| MatrixError::Overflow)?;
+            l[j * n + j] = ljj;
+
+            for i in (j + 1)..n {
+                let mut lij = to_inner_fixed(self.at(i, j));
+                for k in 0..j {
+                    lij =
+                        checked(lij.checked_sub(checked(l[i * n + k].checked_mul(l[j * n + k]))?))?;
+                }
+                l[i * n + j] = checked(lij.checked_div(ljj))?;
+            }
+        }
+
+        let data = l
+            .into_iter()
+            .map(from_inner)
+            .collect::<Result<Vec<_>, _>>()?;
+        Matrix::new(n, n, data)
+    }
+
+    fn at(&self, row: usize, col: usize) -> FixedI64 {
+        self.data[row * self.cols + col]
+    }
+}
+
+fn mul_add(sum: InnerFixed, a: FixedI64, b: FixedI64) -> Result<InnerFixed, MatrixError> {
+    let product = checked(to_inner_fixed(a).checked_mul(to_inner_fixed(b)))?;
+    checked(sum.checked_add(product))
+}
+
+fn checked(x: Option<InnerFixed>) -> Result<InnerFixed, MatrixError> {
+    x.ok_or(MatrixError::Overflow)
+}
+
+fn from_inner(x: InnerFixed) -> Result<FixedI64, MatrixError> {
+    checked_from_inner_fixed(x).ok_or(MatrixError::Overflow)
+}
+
+mod test {
+    #![cfg(test)]
+
+    use super::*;
+
+    #[derive(PartialEq, Debug)]
+    enum Asset {
+        Btc,
+        Eth,
+        Eq,
+    }
+
+    fn matrix(rows: usize, cols: usize, data: &[i64]) -> Matrix {
+        let data = data
+            .iter()
+            .map(|x| FixedI64::saturating_from_integer(*x))
+            .collect();
+        Matrix::new(rows, cols, data).unwrap()
+    }
+
+    #[test]
+    fn construction() {
+        assert_eq!(
+            Matrix::new(2, 2, vec![fx64!(1, 0); 3]),
+            Err(MatrixError::DimensionMismatch)
+        );
+        assert_eq!(Matrix::identity(2), matrix(2, 2, &[1, 0, 0, 1]));
+        assert_eq!(Matrix::zero(1, 2), matrix(1, 2, &[0, 0]));
+
+        let m = matrix(2, 3, &[1, 2, 3, 4, 5, 6]);
+        assert_eq!(m.get(1, 2), Some(fx64!(6, 0)));
+        assert_eq!(m.get(2, 0), None);
+        assert_eq!(m.transpose(), matrix(3, 2, &[1, 4, 2, 5, 3, 6]));
+    }
+
+    #[test]
+    fn from_covariance_map() {
+        let covariances = vec![
+            (Asset::Btc, Asset::Btc, fx64!(0, 04)),
+            (Asset::Eth, Asset::Eth, fx64!(0, 09)),
+            (Asset::Eth, Asset::Btc, fx64!(0, 03)),
+        ];
+        let m = Matrix::from_covariances(&[Asset::Btc, Asset::Eth], &covariances).unwrap();
+
+        assert_eq!(
+            m,
+            Matrix::new(
+                2,
+                2,
+                vec![fx64!(0, 04), fx64!(0, 03), fx64!(0, 03), fx64!(0, 09)]
+            )
+            .unwrap()
+        );
+        assert!(m.is_symmetric());
+        assert_eq!(
+            Matrix::from_covariances(&[Asset::Btc, Asset::Eq], &covariances),
+            Err(MatrixError::MissingCovariance)
+        );
+    }
+
+    #[test]
+    fn multiplication() {
+        let a = matrix(2, 3, &[1, 2, 3, 4, 5, 6]);
+        let b = matrix(3, 2, &[7, 8, 9, 10, 11, 12]);
+
+        assert_eq!(a.checked_mul(&b), Ok(matrix(2, 2, &[58, 64, 139, 154])));
+        assert_eq!(a.checked_mul(&Matrix::identity(3)), Ok(a.clone()));
+        assert_eq!(a.checked_mul(&a), Err(MatrixError::DimensionMismatch));
+        assert_eq!(
+            a.checked_mul_vec(&[fx64!(1, 0), fx64!(0, 5), fx64!(-1, 0)]),
+            Ok(vec![fx64!(-1, 0), fx64!(0, 5)])
+        );
+        assert_eq!(
+            a.checked_mul_vec(&[fx64!(1, 0)]),
+            Err(MatrixError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn portfolio_variance() {
+        let cov = Matrix::new(
+            2,
+            2,
+            vec![fx64!(0, 04), fx64!(0, 03), fx64!(0, 03), fx64!(0, 09)],
+        )
+        .unwrap();
+        let w = [fx64!(0, 6), fx64!(0, 4)];
+
+        // 0.36 * 0.04 + 2 * 0.24 * 0.03 + 0.16 * 0.09
+        assert_eq!(cov.quadratic_form(&w), Ok(fx64!(0, 0432)));
+        assert_eq!(
+            cov.quadratic_form(&[fx64!(1, 0)]),
+            Err(MatrixError::DimensionMismatch)
+        );
+    }
+
+    #[test]
+    fn semidefinite_check() {
+        assert_eq!(
+            matrix(2, 2, &[4, 2, 2, 3]).is_positive_semidefinite(),
+            Ok(true)
+        );
+        // singular, perfectly correlated assets
+        assert_eq!(
+            matrix(2, 2, &[1, 1, 1, 1]).is_positive_semidefinite(),
+            Ok(true)
+        );
+        assert_eq!(
+            matrix(3, 3, &[1, 0, 0, 0, 0, 0, 0, 0, 2]).is_positive_semidefinite(),
+            Ok(true)
+        );
+        assert_eq!(
+            matrix(2, 2, &[1, 2, 2, 1]).is_positive_semidefinite(),
+            Ok(false)
+        );
+        assert_eq!(
+            matrix(2, 2, &[0, 1, 1, 0]).is_positive_semidefinite(),
+            Ok(false)
+        );
+        assert_eq!(matrix(1, 1, &[-1]).is_positive_semidefinite(), Ok(false));
+        assert_eq!(
+            matrix(2, 2, &[1, 2, 3, 4]).is_positive_semidefinite(),
+            Err(MatrixError::NotSymmetric)
+        );
+    }
+
+    #[test]
+    fn cholesky_decomposition() {
+        let m = matrix(3, 3, &[4, 12, -16, 12, 37, -43, -16, -43, 98]);
+        let l = m.cholesky().unwrap();
+
+        assert_eq!(l, matrix(3, 3, &[2, 0, 0, 6, 1, 0, -8, 5, 3]));
+        assert_eq!(l.checked_mul(&l.transpose()), Ok(m));
+
+        let cov = Matrix::new(
+            2,
+            2,
+            vec![fx64!(0, 04), fx64!(0, 03), fx64!(0, 03), fx64!(0, 09)],
+        )
+        .unwrap();
+        let l = cov.cholesky().unwrap();
+        let restored = l.checked_mul(&l.transpose()).unwrap();
+        for i in 0..2 {
+            for j in 0..2 {
+                assert_eq_fx64!(restored.get(i, j).unwrap(), cov.get(i, j).unwrap(), 8);
+            }
+        }
+        assert_eq!(l.get(0, 1), Some(fx64!(0, 0)));
+        assert_eq_fx64!(l.get(1, 1).unwrap(), fx64!(0, 259807621), 5);
+
+        assert_eq!(
+            matrix(2, 2, &[1, 1, 1, 1]).cholesky(),
+            Err(MatrixError::NotPositiveDefinite)
+        );
+        assert_eq!(
+            matrix(2, 3, &[1, 0, 0, 0, 1, 0]).cholesky(),
+            Err(MatrixError::NotSymmetric)
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let max = FixedI64::from_inner(i64::max_value());
+        let m = Matrix::new(1, 1, vec![max]).unwrap();
+
+        assert_eq!(m.checked_mul(&m), Err(MatrixError::Overflow));
+        assert_eq!(m.quadratic_form(&[max]), Err(MatrixError::Overflow));
+    }
+}
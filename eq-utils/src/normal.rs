@@ -0,0 +1,254 @@
+//! Standard normal distribution on `FixedI64`. Everything is computed in `InnerFixed`
+//! from integer constants, so results are the same on every platform.
+
+use super::*;
+use crate::math::{checked_from_inner_fixed, to_inner_fixed, InnerFixed, MathError};
+use substrate_fixed::transcendental;
+
+/// Beyond this distance from the mean the CDF is 0 or 1 and the PDF is 0 at 9 decimals
+const TAIL: i64 = 8;
+
+/// Density of the standard normal distribution. Absolute error is below 1e-8 plus the
+/// error of `exp`, the result saturates to zero for `|x| >= 8`.
+pub fn norm_pdf(x: FixedI64) -> Result<FixedI64, MathError> {
+    from_inner(inner_pdf(to_inner_fixed(x).abs())?)
+}
+
+/// Cumulative distribution function of the standard normal distribution, Abramowitz
+/// and Stegun 26.2.17. Absolute error of the approximation is below 7.5e-8, the result
+/// saturates to 0 for `x <= -8` and to 1 for `x >= 8`.
+pub fn norm_cdf(x: FixedI64) -> Result<FixedI64, MathError> {
+    let x = to_inner_fixed(x);
+    let z = x.abs();
+    let one = InnerFixed::from_num(1);
+    let upper_tail = if z >= InnerFixed::from_num(TAIL) {
+        InnerFixed::from_num(0)
+    } else {
+        let t = checked(one.checked_div(checked(
+            one.checked_add(checked(coef(231_641_900_000_000).checked_mul(z))?),
+        )?))?;
+        let poly = checked(horner(
+            t,
+            &[
+                coef(1_330_274_429_000_000),
+                coef(-1_821_255_978_000_000),
+                coef(1_781_477_937_000_000),
+                coef(-356_563_782_000_000),
+                coef(319_381_530_000_000),
+                InnerFixed::from_num(0),
+            ],
+        ))?;
+        checked(inner_pdf(z)?.checked_mul(poly))?
+    };
+
+    if x >= InnerFixed::from_num(0) {
+        from_inner(one - upper_tail)
+    } else {
+        from_inner(upper_tail)
+    }
+}
+
+/// Quantile function of the standard normal distribution, Acklam's rational
+/// approximation with relative error below 1.15e-9, the total error is dominated by
+/// `ln` and `sqrt` in the tails. Probability must be within `(0, 1)`, so the result
+/// is bounded by the quantile of the smallest positive `FixedI64`, about 5.9978.
+pub fn norm_inv(p: FixedI64) -> Result<FixedI64, MathError> {
+    if p.into_inner() <= 0 || p.into_inner() >= FixedI64::DIV {
+        return Err(MathError::OutOfDomain);
+    }
+    let p = to_inner_fixed(p);
+    let one = InnerFixed::from_num(1);
+    let p_low = coef(24_250_000_000_000);
+
+    let x = if p < p_low {
+        tail_quantile(p)?
+    } else if p <= one - p_low {
+        let q = p - coef(500_000_000_000_000);
+        let r = checked(q.checked_mul(q))?;
+        let num = checked(horner(
+            r,
+            &[
+                coef(-39_696_830_286_653_760),
+                coef(220_946_098_424_520_500),
+                coef(-275_928_510_446_968_700),
+                coef(138_357_751_867_269_000),
+                coef(-30_664_798_066_147_160),
+                coef(2_506_628_277_459_239),
+            ],
+        ))?;
+        let den = checked(horner(
+            r,
+            &[
+                coef(-54_476_098_798_224_060),
+                coef(161_585_836_858_040_900),
+                coef(-155_698_979_859_886_600),
+                coef(66_801_311_887_719_720),
+                coef(-13_280_681_552_885_720),
+                one,
+            ],
+        ))?;
+        checked(checked(num.checked_mul(q))?.checked_div(den))?
+    } else {
+        -tail_quantile(one - p)?
+    };
+    from_inner(x)
+}
+
+/// Quantile of the lower tail `p < 0.02425`
+fn tail_quantile(p: InnerFixed) -> Result<InnerFixed, MathError> {
+    let ln: InnerFixed = transcendental::ln(p).map_err(|_| MathError::Overflow)?;
+    let q: InnerFixed = transcendental::sqrt(checked(ln.checked_mul(InnerFixed::from_num(-2)))?)
+        .map_err(|_| MathError::Overflow)?;
+    let num = checked(horner(
+        q,
+        &[
+            coef(-7_784_894_002_430),
+            coef(-322_396_458_041_136),
+            coef(-2_400_758_277_161_838),
+            coef(-2_549_732_539_343_734),
+            coef(4_374_664_141_464_968),
+            coef(2_938_163_982_698_783),
+        ],
+    ))?;
+    let den = checked(horner(
+        q,
+        &[
+            coef(7_784_695_709_041),
+            coef(322_467_129_070_040),
+            coef(2_445_134_137_142_996),
+            coef(3_754_408_661_907_416),
+            InnerFixed::from_num(1),
+        ],
+    ))?;
+    checked(num.checked_div(den))
+}
+
+/// Density of `|x|`, zero in the tail
+fn inner_pdf(z: InnerFixed) -> Result<InnerFixed, MathError> {
+    if z >= InnerFixed::from_num(TAIL) {
+        return Ok(InnerFixed::from_num(0));
+    }
+    let exponent = -checked(z.checked_mul(z))? / InnerFixed::from_num(2);
+    let e: InnerFixed = transcendental::exp(exponent).map_err(|_| MathError::Overflow)?;
+    // 1 / sqrt(2 * pi)
+    checked(e.checked_mul(coef(398_942_280_401_433)))
+}
+
+/// Polynomial with coefficients from the highest power
+fn horner(x: InnerFixed, coefs: &[InnerFixed]) -> Option<InnerFixed> {
+    coefs.iter().try_fold(InnerFixed::from_num(0), |acc, c| {
+        acc.checked_mul(x)?.checked_add(*c)
+    })
+}
+
+/// Constant given in units of 10^-15
+fn coef(units: i64) -> InnerFixed {
+    InnerFixed::from_num(units) / InnerFixed::from_num(1_000_000_000_000_000i64)
+}
+
+fn checked(x: Option<InnerFixed>) -> Result<InnerFixed, MathError> {
+    x.ok_or(MathError::Overflow)
+}
+
+fn from_inner(x: InnerFixed) -> Result<FixedI64, MathError> {
+    checked_from_inner_fixed(x).ok_or(MathError::Overflow)
+}
+
+mod test {
+    #![cfg(test)]
+
+    use super::*;
+
+    #[test]
+    fn pdf_table() {
+        let table = [
+            (fx64!(0, 0), fx64!(0, 398942280)),
+            (fx64!(0, 5), fx64!(0, 352065327)),
+            (fx64!(-1, 0), fx64!(0, 241970725)),
+            (fx64!(1, 96), fx64!(0, 058440944)),
+            (fx64!(3, 0), fx64!(0, 004431848)),
+            (fx64!(6, 0), fx64!(0, 000000006)),
+        ];
+        for (x, expected) in table.iter() {
+            assert_eq_fx64!(norm_pdf(*x).unwrap(), *expected, 6);
+        }
+    }
+
+    #[test]
+    fn cdf_table() {
+        let table = [
+            (fx64!(-3, 0), fx64!(0, 001349898)),
+            (fx64!(-2, 0), fx64!(0, 022750132)),
+            (fx64!(-1, 0), fx64!(0, 158655254)),
+            (FixedI64::from_inner(-500_000_000), fx64!(0, 308537539)),
+            (fx64!(0, 0), fx64!(0, 5)),
+            (fx64!(0, 5), fx64!(0, 691462461)),
+            (fx64!(1, 0), fx64!(0, 841344746)),
+            (fx64!(1, 96), fx64!(0, 975002105)),
+            (fx64!(2, 0), fx64!(0, 977249868)),
+            (fx64!(3, 0), fx64!(0, 998650102)),
+            (fx64!(6, 0), fx64!(0, 999999999)),
+        ];
+        for (x, expected) in table.iter() {
+            assert_eq_fx64!(norm_cdf(*x).unwrap(), *expected, 6);
+        }
+    }
+
+    #[test]
+    fn inv_table() {
+        let table = [
+            (fx64!(0, 000000001), FixedI64::from_inner(-5_997_807_015)),
+            (fx64!(0, 001), FixedI64::from_inner(-3_090_232_306)),
+            (fx64!(0, 025), FixedI64::from_inner(-1_959_963_985)),
+            (fx64!(0, 05), FixedI64::from_inner(-1_644_853_627)),
+            (fx64!(0, 1), FixedI64::from_inner(-1_281_551_566)),
+            (fx64!(0, 3), FixedI64::from_inner(-524_400_513)),
+            (fx64!(0, 5), fx64!(0, 0)),
+            (fx64!(0, 7), fx64!(0, 524400513)),
+            (fx64!(0, 9), fx64!(1, 281551566)),
+            (fx64!(0, 975), fx64!(1, 959963985)),
+            (fx64!(0, 999), fx64!(3, 090232306)),
+            (fx64!(0, 999999999), fx64!(5, 997807015)),
+        ];
+        for (p, expected) in table.iter() {
+            assert_eq_fx64!(norm_inv(*p).unwrap(), *expected, 4);
+        }
+    }
+
+    #[test]
+    fn inv_of_cdf() {
+        for x in [
+            -2_500_000_000i64,
+            -700_000_000,
+            0,
+            300_000_000,
+            1_800_000_000,
+        ]
+        .iter()
+        {
+            let x = FixedI64::from_inner(*x);
+            assert_eq_fx64!(norm_inv(norm_cdf(x).unwrap()).unwrap(), x, 4);
+        }
+    }
+
+    #[test]
+    fn tails_saturate() {
+        let max = FixedI64::from_inner(i64::max_value());
+        let min = FixedI64::from_inner(i64::min_value());
+
+        assert_eq!(norm_cdf(fx64!(8, 0)), Ok(fx64!(1, 0)));
+        assert_eq!(norm_cdf(max), Ok(fx64!(1, 0)));
+        assert_eq!(norm_cdf(fx64!(-10, 0)), Ok(fx64!(0, 0)));
+        assert_eq!(norm_cdf(min), Ok(fx64!(0, 0)));
+        assert_eq!(norm_pdf(max), Ok(fx64!(0, 0)));
+        assert_eq!(norm_pdf(min), Ok(fx64!(0, 0)));
+    }
+
+    #[test]
+    fn inv_outside_of_domain() {
+        assert_eq!(norm_inv(fx64!(0, 0)), Err(MathError::OutOfDomain));
+        assert_eq!(norm_inv(fx64!(1, 0)), Err(MathError::OutOfDomain));
+        assert_eq!(norm_inv(fx64!(-1, 0)), Err(MathError::OutOfDomain));
+        assert_eq!(norm_inv(fx64!(2, 0)), Err(MathError::OutOfDomain));
+    }
+}
@@ -0,0 +1,214 @@
+//! Sample statistics over `FixedI64` series, computed with 64 fractional bits and
+//! rounded once into the result
+
+use super::*;
+use crate::math::{checked_from_inner_fixed, to_inner_fixed, InnerFixed};
+use sp_std::prelude::*;
+use substrate_fixed::transcendental;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum StatsError {
+    /// Series is shorter than the statistic requires
+    NotEnoughValues,
+    /// Paired series have different lengths
+    LengthMismatch,
+    /// Series is constant, correlation is undefined
+    ZeroVariance,
+    /// Log-return of a price that is zero or negative
+    NonPositivePrice,
+    /// Intermediate value or result doesn't fit into the fixed point range
+    Overflow,
+}
+
+/// Arithmetic mean, at least one value is required
+pub fn mean(xs: &[FixedI64]) -> Result<FixedI64, StatsError> {
+    from_inner(inner_mean(xs)?)
+}
+
+/// Sample variance with `n - 1` degrees of freedom, at least two values are required
+pub fn variance(xs: &[FixedI64]) -> Result<FixedI64, StatsError> {
+    from_inner(inner_covariance(xs, xs)?)
+}
+
+/// Sample standard deviation, square root of `variance`
+pub fn std_dev(xs: &[FixedI64]) -> Result<FixedI64, StatsError> {
+    from_inner(inner_sqrt(inner_covariance(xs, xs)?)?)
+}
+
+/// Sample covariance of paired series with `n - 1` degrees of freedom
+pub fn covariance(xs: &[FixedI64], ys: &[FixedI64]) -> Result<FixedI64, StatsError> {
+    from_inner(inner_covariance(xs, ys)?)
+}
+
+/// Pearson correlation coefficient of paired series, within `[-1, 1]`
+pub fn correlation(xs: &[FixedI64], ys: &[FixedI64]) -> Result<FixedI64, StatsError> {
+    let cov = inner_covariance(xs, ys)?;
+    let sd_x = inner_sqrt(inner_covariance(xs, xs)?)?;
+    let sd_y = inner_sqrt(inner_covariance(ys, ys)?)?;
+    if sd_x == InnerFixed::from_num(0) || sd_y == InnerFixed::from_num(0) {
+        return Err(StatsError::ZeroVariance);
+    }
+
+    let one = InnerFixed::from_num(1);
+    let r = checked(cov.checked_div(sd_x))?;
+    let r = checked(r.checked_div(sd_y))?;
+    // rounding may push perfectly correlated series slightly out of range
+    from_inner(r.max(-one).min(one))
+}
+
+/// Logarithms of the ratios of consecutive prices, one value shorter than `prices`
+pub fn log_returns(prices: &[FixedI64]) -> Result<Vec<FixedI64>, StatsError> {
+    if prices.len() < 2 {
+        return Err(StatsError::NotEnoughValues);
+    }
+    if prices.iter().any(|p| p.into_inner() <= 0) {
+        return Err(StatsError::NonPositivePrice);
+    }
+
+    prices
+        .windows(2)
+        .map(|w| {
+            let ratio = checked(to_inner_fixed(w[1]).checked_div(to_inner_fixed(w[0])))?;
+            let ln: InnerFixed = transcendental::ln(ratio).map_err(|_| StatsError::Overflow)?;
+            from_inner(ln)
+        })
+        .collect()
+}
+
+fn inner_mean(xs: &[FixedI64]) -> Result<InnerFixed, StatsError> {
+    if xs.is_empty() {
+        return Err(StatsError::NotEnoughValues);
+    }
+    let sum = xs.iter().try_fold(InnerFixed::from_num(0), |sum, x| {
+        checked(sum.checked_add(to_inner_fixed(*x)))
+    })?;
+    checked(sum.checked_div(checked(InnerFixed::checked_from_num(xs.len()))?))
+}
+
+fn inner_covariance(xs: &[FixedI64], ys: &[FixedI64]) -> Result<InnerFixed, StatsError> {
+    if xs.len() != ys.len() {
+        return Err(StatsError::LengthMismatch);
+    }
+    if xs.len() < 2 {
+        return Err(StatsError::NotEnoughValues);
+    }
+
+    let mean_x = inner_mean(xs)?;
+    let mean_y = inner_mean(ys)?;
+    let sum = xs
+        .iter()
+        .zip(ys.iter())
+        .try_fold(InnerFixed::from_num(0), |sum, (x, y)| {
+            let dx = checked(to_inner_fixed(*x).checked_sub(mean_x))?;
+            let dy = checked(to_inner_fixed(*y).checked_sub(mean_y))?;
+            checked(sum.checked_add(checked(dx.checked_mul(dy))?))
+        })?;
+    checked(sum.checked_div(checked(InnerFixed::checked_from_num(xs.len() - 1))?))
+}
+
+fn inner_sqrt(x: InnerFixed) -> Result<InnerFixed, StatsError> {
+    if x == InnerFixed::from_num(0) {
+        return Ok(x);
+    }
+    transcendental::sqrt(x).map_err(|_| StatsError::Overflow)
+}
+
+fn checked(x: Option<InnerFixed>) -> Result<InnerFixed, StatsError> {
+    x.ok_or(StatsError::Overflow)
+}
+
+fn from_inner(x: InnerFixed) -> Result<FixedI64, StatsError> {
+    checked_from_inner_fixed(x).ok_or(StatsError::Overflow)
+}
+
+mod test {
+    #![cfg(test)]
+
+    use super::*;
+
+    fn series(xs: &[i64]) -> Vec<FixedI64> {
+        xs.iter()
+            .map(|x| FixedI64::saturating_from_integer(*x))
+            .collect()
+    }
+
+    #[test]
+    fn mean_and_dispersion() {
+        let xs = series(&[2, 4, 4, 4, 5, 5, 7, 9]);
+
+        assert_eq!(mean(&xs), Ok(fx64!(5, 0)));
+        assert_eq_fx64!(variance(&xs).unwrap(), fx64!(4, 571428571), 8);
+        assert_eq_fx64!(std_dev(&xs).unwrap(), fx64!(2, 138089935), 5);
+    }
+
+    #[test]
+    fn fractional_values() {
+        let xs = vec![fx64!(0, 1), fx64!(0, 2), fx64!(0, 35)];
+
+        assert_eq_fx64!(mean(&xs).unwrap(), fx64!(0, 216666667), 8);
+        assert_eq_fx64!(variance(&xs).unwrap(), fx64!(0, 015833333), 8);
+        assert_eq_fx64!(std_dev(&xs).unwrap(), fx64!(0, 125830574), 5);
+    }
+
+    #[test]
+    fn covariance_and_correlation() {
+        let xs = series(&[1, 2, 3, 4, 5]);
+        let ys = series(&[2, 4, 5, 4, 5]);
+
+        assert_eq!(covariance(&xs, &ys), Ok(fx64!(1, 5)));
+        assert_eq_fx64!(correlation(&xs, &ys).unwrap(), fx64!(0, 774596669), 5);
+        assert_eq!(covariance(&xs, &xs), variance(&xs));
+    }
+
+    #[test]
+    fn perfect_correlation_stays_in_range() {
+        let xs = vec![fx64!(0, 1), fx64!(0, 3), fx64!(0, 7)];
+        let ys: Vec<_> = xs.iter().map(|x| fx64!(0, 0) - *x).collect();
+
+        let r = correlation(&xs, &xs).unwrap();
+        assert!(r <= fx64!(1, 0));
+        assert_eq_fx64!(r, fx64!(1, 0), 5);
+
+        let r = correlation(&xs, &ys).unwrap();
+        assert!(r >= fx64!(-1, 0));
+        assert_eq_fx64!(r, fx64!(-1, 0), 5);
+    }
+
+    #[test]
+    fn log_returns_of_prices() {
+        let prices = series(&[100, 110, 99]);
+        let returns = log_returns(&prices).unwrap();
+
+        assert_eq!(returns.len(), 2);
+        assert_eq_fx64!(returns[0], fx64!(0, 095310180), 4);
+        assert_eq_fx64!(returns[1], FixedI64::from_inner(-105_360_516), 4);
+    }
+
+    #[test]
+    fn degenerate_input() {
+        let one = series(&[1]);
+        let flat = series(&[3, 3, 3]);
+        let xs = series(&[1, 2, 3]);
+
+        assert_eq!(mean(&[]), Err(StatsError::NotEnoughValues));
+        assert_eq!(variance(&one), Err(StatsError::NotEnoughValues));
+        assert_eq!(std_dev(&[]), Err(StatsError::NotEnoughValues));
+        assert_eq!(covariance(&xs, &one), Err(StatsError::LengthMismatch));
+        assert_eq!(correlation(&xs, &flat), Err(StatsError::ZeroVariance));
+        assert_eq!(std_dev(&flat), Ok(fx64!(0, 0)));
+        assert_eq!(log_returns(&one), Err(StatsError::NotEnoughValues));
+        assert_eq!(
+            log_returns(&series(&[1, 0, 2])),
+            Err(StatsError::NonPositivePrice)
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let max = FixedI64::from_inner(i64::max_value());
+        let min = FixedI64::from_inner(i64::min_value());
+
+        assert_eq!(mean(&[max, max]), Ok(max));
+        assert_eq!(variance(&[max, min]), Err(StatsError::Overflow));
+    }
+}
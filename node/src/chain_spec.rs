@@ -1,7 +1,7 @@
 use eq_node_runtime::{
-    eq_primitives, opaque::SessionKeys, AccountId, AuraConfig, BalancesConfig, ClaimConfig,
-    EqVestingConfig, GenesisConfig, GrandpaConfig, SessionConfig, Signature, SudoConfig,
-    SystemConfig, WASM_BINARY,
+    eq_balances, eq_primitives, opaque::SessionKeys, AccountId, AuraConfig, BalancesConfig,
+    ClaimConfig, EqAssetsConfig, EqVestingConfig, GenesisConfig, GrandpaConfig, SessionConfig,
+    Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -109,6 +109,29 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
     ))
 }
 
+/// Built-in currencies listed in the asset registry with their tickers and decimals
+fn genesis_assets() -> Vec<(
+    eq_primitives::currency::Currency,
+    eq_primitives::asset::AssetData<eq_node_runtime::Balance>,
+)> {
+    use eq_primitives::asset::{AssetData, AssetStatus};
+    use eq_primitives::currency::Currency;
+
+    Currency::iterator_with_usd()
+        .map(|currency| {
+            (
+                *currency,
+                AssetData {
+                    symbol: currency.to_string().into_bytes(),
+                    decimals: currency.decimals(),
+                    existential_deposit: 0,
+                    status: AssetStatus::Active,
+                },
+            )
+        })
+        .collect()
+}
+
 fn testnet_genesis(
     initial_authorities: Vec<(AccountId, AccountId, GrandpaId, AuraId)>,
     root_key: AccountId,
@@ -120,11 +143,20 @@ fn testnet_genesis(
             code: WASM_BINARY.to_vec(),
             changes_trie_config: Default::default(),
         }),
+        eq_assets: Some(EqAssetsConfig {
+            assets: genesis_assets(),
+        }),
         eq_balances: Some(BalancesConfig {
             balances: endowed_accounts
                 .iter()
                 .cloned()
-                .map(|k| (k, 1 << 50, eq_primitives::currency::Currency::Eq.value()))
+                .map(|k| {
+                    (
+                        k,
+                        eq_primitives::currency::Currency::Eq,
+                        eq_balances::SignedBalance::Positive(1 << 50),
+                    )
+                })
                 .collect(),
         }),
         aura: Some(AuraConfig {
@@ -0,0 +1,34 @@
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+
+use crate::Module as Assets;
+use frame_benchmarking::benchmarks;
+use frame_system::RawOrigin;
+
+fn asset_data<T: Trait>() -> AssetData<T::Balance> {
+    AssetData {
+        symbol: b"DOT".to_vec(),
+        decimals: 9,
+        existential_deposit: T::Balance::from(1_000 as u32),
+        status: AssetStatus::Active,
+    }
+}
+
+benchmarks! {
+    _ { }
+
+    add_asset {
+        let b in 0 .. 100;
+    }: _ (RawOrigin::Root, Currency::Other(100), asset_data::<T>())
+
+    update_asset {
+        let b in 0 .. 100;
+        Assets::<T>::add_asset(RawOrigin::Root.into(), Currency::Other(100), asset_data::<T>())?;
+    }: _ (RawOrigin::Root, Currency::Other(100), b"DOTS".to_vec(), 10, T::Balance::from(10_000 as u32))
+
+    set_asset_status {
+        let b in 0 .. 100;
+        Assets::<T>::add_asset(RawOrigin::Root.into(), Currency::Other(100), asset_data::<T>())?;
+    }: _ (RawOrigin::Root, Currency::Other(100), AssetStatus::Frozen)
+}
@@ -0,0 +1,21 @@
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+impl crate::WeightInfo for () {
+    fn add_asset(b: u32) -> Weight {
+        (21350000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn update_asset(b: u32) -> Weight {
+        (22180000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+    fn set_asset_status(b: u32) -> Weight {
+        (20440000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+    }
+}
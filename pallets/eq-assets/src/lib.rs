@@ -0,0 +1,167 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use eq_primitives::asset::{AssetData, AssetGetter, AssetStatus};
+use eq_primitives::currency::Currency;
+use frame_support::storage::IterableStorageMap;
+use frame_support::{
+    decl_error, decl_event, decl_module, decl_storage, ensure, weights::Weight, Parameter,
+};
+use frame_system::ensure_root;
+use sp_runtime::{
+    traits::{AtLeast32BitUnsigned, MaybeSerializeDeserialize, Member},
+    DispatchResult,
+};
+use sp_std::prelude::*;
+
+mod benchmarking;
+mod benchmarks;
+mod mock;
+mod tests;
+
+/// Maximum length of the asset symbol in bytes
+pub const MAX_SYMBOL_LENGTH: usize = 8;
+
+pub trait WeightInfo {
+    fn add_asset(b: u32) -> Weight;
+    fn update_asset(b: u32) -> Weight;
+    fn set_asset_status(b: u32) -> Weight;
+}
+
+pub trait Trait: frame_system::Trait {
+    /// The overarching event type.
+    type Event: From<Event> + Into<<Self as frame_system::Trait>::Event>;
+
+    /// Balance type of the existential deposits
+    type Balance: Parameter
+        + Member
+        + AtLeast32BitUnsigned
+        + Default
+        + Copy
+        + MaybeSerializeDeserialize;
+
+    /// Weight information for extrinsics in this pallet.
+    type WeightInfo: WeightInfo;
+}
+
+decl_storage! {
+    trait Store for Module<T: Trait> as EqAssets {
+        /// Listed assets with their metadata and status
+        pub Assets get(fn asset): map hasher(blake2_128_concat) Currency => Option<AssetData<T::Balance>>;
+    }
+    add_extra_genesis {
+        config(assets): Vec<(Currency, AssetData<T::Balance>)>;
+        build(|config: &GenesisConfig<T>| {
+            for (currency, asset) in config.assets.iter() {
+                assert!(Module::<T>::ensure_listable(currency, asset).is_ok(), "Invalid genesis asset");
+                assert!(!<Assets<T>>::contains_key(currency), "Duplicate genesis asset");
+                <Assets<T>>::insert(currency, asset);
+            }
+        });
+    }
+}
+
+decl_event!(
+    pub enum Event {
+        /// New asset is listed. [currency]
+        AssetAdded(Currency),
+        /// Symbol, decimals or existential deposit of the asset changed. [currency]
+        AssetUpdated(Currency),
+        /// Status of the asset changed. [currency, status]
+        AssetStatusChanged(Currency, AssetStatus),
+    }
+);
+
+decl_error! {
+    pub enum Error for Module<T: Trait> {
+        /// `Currency::Unknown` can't be listed
+        UnknownCurrency,
+        /// Asset of the currency is already listed
+        AssetExists,
+        /// Asset of the currency is not listed
+        AssetNotFound,
+        /// Symbol is empty or longer than `MAX_SYMBOL_LENGTH`
+        InvalidSymbol,
+    }
+}
+
+decl_module! {
+    pub struct Module<T: Trait> for enum Call where origin: T::Origin {
+        type Error = Error<T>;
+
+        fn deposit_event() = default;
+
+        /// Lists a new asset. The dispatch origin for this call must be _Root_.
+        #[weight = T::WeightInfo::add_asset(1)]
+        pub fn add_asset(origin, currency: Currency, asset: AssetData<T::Balance>) -> DispatchResult {
+            ensure_root(origin)?;
+            Self::ensure_listable(&currency, &asset)?;
+            ensure!(!<Assets<T>>::contains_key(&currency), Error::<T>::AssetExists);
+
+            <Assets<T>>::insert(&currency, asset);
+            Self::deposit_event(Event::AssetAdded(currency));
+            Ok(())
+        }
+
+        /// Changes symbol, decimals and existential deposit of the listed asset, status is
+        /// kept. The dispatch origin for this call must be _Root_.
+        #[weight = T::WeightInfo::update_asset(1)]
+        pub fn update_asset(
+            origin,
+            currency: Currency,
+            symbol: Vec<u8>,
+            decimals: u8,
+            existential_deposit: T::Balance,
+        ) -> DispatchResult {
+            ensure_root(origin)?;
+            <Assets<T>>::try_mutate(&currency, |maybe_asset| -> DispatchResult {
+                let asset = maybe_asset.as_mut().ok_or(Error::<T>::AssetNotFound)?;
+                let updated = AssetData { symbol, decimals, existential_deposit, status: asset.status };
+                Self::ensure_listable(&currency, &updated)?;
+                *asset = updated;
+                Ok(())
+            })?;
+            Self::deposit_event(Event::AssetUpdated(currency));
+            Ok(())
+        }
+
+        /// Freezes, delists or reactivates the listed asset. The dispatch origin for this
+        /// call must be _Root_.
+        #[weight = T::WeightInfo::set_asset_status(1)]
+        pub fn set_asset_status(origin, currency: Currency, status: AssetStatus) -> DispatchResult {
+            ensure_root(origin)?;
+            <Assets<T>>::try_mutate(&currency, |maybe_asset| -> DispatchResult {
+                let asset = maybe_asset.as_mut().ok_or(Error::<T>::AssetNotFound)?;
+                asset.status = status;
+                Ok(())
+            })?;
+            Self::deposit_event(Event::AssetStatusChanged(currency, status));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    fn ensure_listable(currency: &Currency, asset: &AssetData<T::Balance>) -> DispatchResult {
+        ensure!(*currency != Currency::Unknown, Error::<T>::UnknownCurrency);
+        ensure!(
+            !asset.symbol.is_empty() && asset.symbol.len() <= MAX_SYMBOL_LENGTH,
+            Error::<T>::InvalidSymbol
+        );
+        Ok(())
+    }
+}
+
+impl<T: Trait> AssetGetter<T::Balance> for Module<T> {
+    fn get_asset(currency: &Currency) -> Option<AssetData<T::Balance>> {
+        Self::asset(currency)
+    }
+
+    /// All listed currencies including frozen and delisted ones, ordered by id
+    fn currencies() -> Vec<Currency> {
+        let mut currencies = <Assets<T>>::iter()
+            .map(|(currency, _)| currency)
+            .collect::<Vec<_>>();
+        currencies.sort_by_key(Currency::value);
+        currencies
+    }
+}
@@ -0,0 +1,126 @@
+#![cfg(test)]
+
+use super::*;
+use crate::mock::{asset, new_test_ext, ModuleAssets, Origin, Test};
+use frame_support::{assert_noop, assert_ok};
+use sp_runtime::DispatchError;
+
+#[test]
+fn genesis_assets() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            ModuleAssets::get_asset(&Currency::Btc),
+            Some(asset(b"BTC", 9, 1_000))
+        );
+        assert_eq!(ModuleAssets::get_asset(&Currency::Eth), None);
+        assert_eq!(
+            ModuleAssets::currencies(),
+            vec![Currency::Eq, Currency::Btc]
+        );
+    });
+}
+
+#[test]
+fn add_asset() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ModuleAssets::add_asset(
+            Origin::root(),
+            Currency::Other(100),
+            asset(b"DOT", 10, 100)
+        ));
+        assert_eq!(
+            ModuleAssets::get_asset(&Currency::Other(100)),
+            Some(asset(b"DOT", 10, 100))
+        );
+        assert_eq!(
+            ModuleAssets::currencies(),
+            vec![Currency::Eq, Currency::Btc, Currency::Other(100)]
+        );
+
+        assert_noop!(
+            ModuleAssets::add_asset(Origin::signed(1), Currency::Eth, asset(b"ETH", 9, 1)),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ModuleAssets::add_asset(Origin::root(), Currency::Other(4), asset(b"BTC", 9, 1)),
+            Error::<Test>::AssetExists
+        );
+        assert_noop!(
+            ModuleAssets::add_asset(Origin::root(), Currency::Unknown, asset(b"UNK", 9, 1)),
+            Error::<Test>::UnknownCurrency
+        );
+        assert_noop!(
+            ModuleAssets::add_asset(Origin::root(), Currency::Eth, asset(b"", 9, 1)),
+            Error::<Test>::InvalidSymbol
+        );
+        assert_noop!(
+            ModuleAssets::add_asset(Origin::root(), Currency::Eth, asset(b"ETHEREUM1", 9, 1)),
+            Error::<Test>::InvalidSymbol
+        );
+    });
+}
+
+#[test]
+fn update_asset_keeps_status() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ModuleAssets::set_asset_status(
+            Origin::root(),
+            Currency::Btc,
+            AssetStatus::Frozen
+        ));
+        assert_ok!(ModuleAssets::update_asset(
+            Origin::root(),
+            Currency::Btc,
+            b"XBT".to_vec(),
+            8,
+            10
+        ));
+        assert_eq!(
+            ModuleAssets::get_asset(&Currency::Btc),
+            Some(AssetData {
+                symbol: b"XBT".to_vec(),
+                decimals: 8,
+                existential_deposit: 10,
+                status: AssetStatus::Frozen,
+            })
+        );
+
+        assert_noop!(
+            ModuleAssets::update_asset(Origin::root(), Currency::Eth, b"ETH".to_vec(), 9, 1),
+            Error::<Test>::AssetNotFound
+        );
+        assert_noop!(
+            ModuleAssets::update_asset(Origin::root(), Currency::Btc, vec![], 9, 1),
+            Error::<Test>::InvalidSymbol
+        );
+    });
+}
+
+#[test]
+fn set_asset_status() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ModuleAssets::set_asset_status(
+            Origin::root(),
+            Currency::Eq,
+            AssetStatus::Delisted
+        ));
+        assert_eq!(
+            ModuleAssets::get_asset(&Currency::Eq).map(|a| a.status),
+            Some(AssetStatus::Delisted)
+        );
+        // delisted assets stay in the registry
+        assert_eq!(
+            ModuleAssets::currencies(),
+            vec![Currency::Eq, Currency::Btc]
+        );
+
+        assert_noop!(
+            ModuleAssets::set_asset_status(Origin::signed(1), Currency::Eq, AssetStatus::Active),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            ModuleAssets::set_asset_status(Origin::root(), Currency::Eos, AssetStatus::Active),
+            Error::<Test>::AssetNotFound
+        );
+    });
+}
@@ -0,0 +1,28 @@
+//! Runtime API definition for the eq-balances pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use eq_balances::{BalancesAggregate, SignedBalance};
+use eq_primitives::currency::Currency;
+use sp_runtime::traits::Member;
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+    /// Reads balances and aggregates without decoding raw storage keys
+    #[api_version(2)]
+    pub trait EqBalancesApi<AccountId, Balance, BlockNumber> where
+        AccountId: Codec,
+        Balance: Codec + Member,
+        BlockNumber: Codec,
+    {
+        /// Signed balance of the account in the currency
+        fn balance(who: AccountId, currency: Currency) -> SignedBalance<Balance>;
+        /// All non-zero balances of the account
+        fn all_balances(who: AccountId) -> Vec<(Currency, SignedBalance<Balance>)>;
+        /// Total issuance and debt of the currency
+        fn aggregates(currency: Currency) -> BalancesAggregate<Balance>;
+        /// Balance of the account as of the latest checkpoint at or before the block
+        fn balance_at(who: AccountId, currency: Currency, block: BlockNumber) -> Option<SignedBalance<Balance>>;
+    }
+}
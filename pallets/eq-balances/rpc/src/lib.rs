@@ -0,0 +1,148 @@
+//! RPC interface for the eq-balances pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use eq_balances::{BalancesAggregate, SignedBalance};
+pub use eq_balances_rpc_runtime_api::EqBalancesApi as EqBalancesRuntimeApi;
+use eq_primitives::currency::Currency;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, Member},
+};
+
+/// Balances of accounts in different currencies
+#[rpc]
+pub trait EqBalancesApi<BlockHash, AccountId, Balance: Member, BlockNumber> {
+    /// Signed balance of the account in the currency
+    #[rpc(name = "eqBalances_getBalance")]
+    fn get_balance(
+        &self,
+        who: AccountId,
+        currency: Currency,
+        at: Option<BlockHash>,
+    ) -> Result<SignedBalance<Balance>>;
+
+    /// All non-zero balances of the account
+    #[rpc(name = "eqBalances_getAllBalances")]
+    fn get_all_balances(
+        &self,
+        who: AccountId,
+        at: Option<BlockHash>,
+    ) -> Result<Vec<(Currency, SignedBalance<Balance>)>>;
+
+    /// Total issuance and debt of the currency
+    #[rpc(name = "eqBalances_getAggregates")]
+    fn get_aggregates(
+        &self,
+        currency: Currency,
+        at: Option<BlockHash>,
+    ) -> Result<BalancesAggregate<Balance>>;
+
+    /// Balance of the account as of the latest checkpoint at or before the block
+    #[rpc(name = "eqBalances_getBalanceAt")]
+    fn get_balance_at(
+        &self,
+        who: AccountId,
+        currency: Currency,
+        block: BlockNumber,
+        at: Option<BlockHash>,
+    ) -> Result<Option<SignedBalance<Balance>>>;
+}
+
+/// Implements `EqBalancesApi` by calling into the runtime
+pub struct EqBalances<C, B> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> EqBalances<C, B> {
+    /// Creates new `EqBalances` with the given reference to the client
+    pub fn new(client: Arc<C>) -> Self {
+        EqBalances {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Error type of this RPC api
+pub enum Error {
+    /// The call to runtime failed
+    RuntimeError,
+}
+
+impl From<Error> for i64 {
+    fn from(e: Error) -> i64 {
+        match e {
+            Error::RuntimeError => 1,
+        }
+    }
+}
+
+fn runtime_error(e: impl std::fmt::Debug) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(Error::RuntimeError.into()),
+        message: "Unable to query balances.".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
+
+impl<C, Block, AccountId, Balance, BlockNumber>
+    EqBalancesApi<<Block as BlockT>::Hash, AccountId, Balance, BlockNumber> for EqBalances<C, Block>
+where
+    Block: BlockT,
+    C: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync,
+    C::Api: EqBalancesRuntimeApi<Block, AccountId, Balance, BlockNumber>,
+    AccountId: Codec,
+    Balance: Codec + Member,
+    BlockNumber: Codec,
+{
+    fn get_balance(
+        &self,
+        who: AccountId,
+        currency: Currency,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<SignedBalance<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.balance(&at, who, currency).map_err(runtime_error)
+    }
+
+    fn get_all_balances(
+        &self,
+        who: AccountId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Vec<(Currency, SignedBalance<Balance>)>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.all_balances(&at, who).map_err(runtime_error)
+    }
+
+    fn get_aggregates(
+        &self,
+        currency: Currency,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<BalancesAggregate<Balance>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.aggregates(&at, currency).map_err(runtime_error)
+    }
+
+    fn get_balance_at(
+        &self,
+        who: AccountId,
+        currency: Currency,
+        block: BlockNumber,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<SignedBalance<Balance>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+        api.balance_at(&at, who, currency, block)
+            .map_err(runtime_error)
+    }
+}
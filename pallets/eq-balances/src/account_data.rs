@@ -0,0 +1,74 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use crate::signed_balance::SignedBalance;
+use codec::{Decode, Encode};
+use eq_primitives::currency::Currency;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{
+    traits::{AtLeast32Bit, Member, Zero},
+    RuntimeDebug,
+};
+use sp_std::prelude::*;
+
+/// Balances of an account in all currencies, stored as a single entry.
+/// Holds at most one non-zero balance per currency id, ordered by it, so it never
+/// has more than 256 entries.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct AccountData<Balance>
+where
+    Balance: Member,
+{
+    balances: Vec<(Currency, SignedBalance<Balance>)>,
+}
+
+impl<Balance> Default for AccountData<Balance>
+where
+    Balance: Member,
+{
+    fn default() -> AccountData<Balance> {
+        AccountData {
+            balances: Vec::new(),
+        }
+    }
+}
+
+impl<Balance> AccountData<Balance>
+where
+    Balance: Member + AtLeast32Bit,
+{
+    /// Balance in the currency, zero if there is none
+    pub fn balance(&self, currency: &Currency) -> SignedBalance<Balance> {
+        self.position(currency)
+            .ok()
+            .map(|i| self.balances[i].1.clone())
+            .unwrap_or_else(SignedBalance::zero)
+    }
+
+    /// Replaces balance in the currency, zero balances are removed
+    pub fn set_balance(&mut self, currency: &Currency, balance: SignedBalance<Balance>) {
+        match (self.position(currency), balance.is_zero()) {
+            (Ok(i), true) => {
+                self.balances.remove(i);
+            }
+            (Ok(i), false) => self.balances[i].1 = balance,
+            (Err(_), true) => {}
+            (Err(i), false) => self.balances.insert(i, (*currency, balance)),
+        }
+    }
+
+    /// All non-zero balances ordered by currency
+    pub fn balances(&self) -> &[(Currency, SignedBalance<Balance>)] {
+        &self.balances
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.balances.is_empty()
+    }
+
+    fn position(&self, currency: &Currency) -> Result<usize, usize> {
+        let key = currency.value();
+        self.balances.binary_search_by_key(&key, |(c, _)| c.value())
+    }
+}
@@ -0,0 +1,223 @@
+use super::*;
+use frame_support::storage::{unhashed, StoragePrefixedMap};
+
+/// Aggregates computed per currency
+pub type Totals<Balance> = Vec<(currency::Currency, BalancesAggregate<Balance>)>;
+
+/// Currency with its stored and actual aggregates
+pub type AggregatesDrift<Balance> = (
+    currency::Currency,
+    BalancesAggregate<Balance>,
+    BalancesAggregate<Balance>,
+);
+
+/// Progress of the `BalancesAggregates` repair. Entries of `Accounts` are processed in
+/// the order of their storage keys, then entries of `Reserves`.
+#[derive(Encode, Decode, Clone, Default, PartialEq, RuntimeDebug)]
+pub struct AggregatesRepairState<Balance> {
+    /// Storage key of the last processed entry, or prefix of the map being started
+    pub last_key: Vec<u8>,
+    /// Totals of the processed accounts
+    pub totals: Totals<Balance>,
+}
+
+impl<Balance: Member + AtLeast32BitUnsigned + Copy> BalancesAggregate<Balance> {
+    /// Adds the balance to the totals, `None` on overflow
+    pub fn checked_add(&self, balance: &SignedBalance<Balance>) -> Option<Self> {
+        let mut aggregate = self.clone();
+        match balance {
+            Positive(p) => aggregate.total_issuance = aggregate.total_issuance.checked_add(p)?,
+            Negative(n) => aggregate.total_debt = aggregate.total_debt.checked_add(n)?,
+        }
+        Some(aggregate)
+    }
+
+    /// Removes the balance from the totals, `None` if totals are lower than the balance
+    pub fn checked_sub(&self, balance: &SignedBalance<Balance>) -> Option<Self> {
+        let mut aggregate = self.clone();
+        match balance {
+            Positive(p) => aggregate.total_issuance = aggregate.total_issuance.checked_sub(p)?,
+            Negative(n) => aggregate.total_debt = aggregate.total_debt.checked_sub(n)?,
+        }
+        Some(aggregate)
+    }
+}
+
+/// Aggregate of the currency in `totals`, added when missing
+fn aggregate_of<'a, Balance: Default>(
+    totals: &'a mut Totals<Balance>,
+    currency: &currency::Currency,
+) -> &'a mut BalancesAggregate<Balance> {
+    let index = match totals.iter().position(|(c, _)| c == currency) {
+        Some(index) => index,
+        None => {
+            totals.push((*currency, BalancesAggregate::default()));
+            totals.len() - 1
+        }
+    };
+    &mut totals[index].1
+}
+
+impl<T: Trait> Module<T> {
+    /// Compares `BalancesAggregates` with the totals of all accounts, returns stored and
+    /// actual aggregates of every currency that drifted. Reads the whole state, so it is
+    /// meant for RPC and tests, use `repair_aggregates` on chain.
+    pub fn audit_aggregates() -> Vec<AggregatesDrift<T::Balance>> {
+        match Self::compute_aggregates() {
+            Ok(totals) => Self::aggregates_drift(totals),
+            Err(_) => {
+                eq_error!(target: LOG_TARGET, "aggregates overflow, audit skipped");
+                Vec::new()
+            }
+        }
+    }
+
+    /// Totals of all balances and reserves
+    pub(crate) fn compute_aggregates() -> Result<Totals<T::Balance>, DispatchError> {
+        let mut totals = Vec::new();
+        for (_, account) in <Accounts<T>>::iter() {
+            for (currency, balance) in account.balances() {
+                Self::add_to_totals(&mut totals, currency, balance)?;
+            }
+        }
+        for (_, currency, reserves) in <Reserves<T>>::iter() {
+            for reserve in reserves.iter() {
+                Self::add_to_totals(&mut totals, &currency, &Positive(reserve.amount))?;
+            }
+        }
+        Ok(totals)
+    }
+
+    /// Stored and actual aggregates of the currencies where they differ
+    fn aggregates_drift(mut totals: Totals<T::Balance>) -> Vec<AggregatesDrift<T::Balance>> {
+        for (currency, _) in <BalancesAggregates<T>>::iter() {
+            aggregate_of(&mut totals, &currency);
+        }
+        totals
+            .into_iter()
+            .filter_map(|(currency, actual)| {
+                let stored = <BalancesAggregates<T>>::get(&currency);
+                if stored == actual {
+                    None
+                } else {
+                    Some((currency, stored, actual))
+                }
+            })
+            .collect()
+    }
+
+    /// Adds up to `max_entries` entries of `Accounts` and `Reserves` to the repair totals.
+    /// After the last entry every drifted aggregate is reported and overwritten.
+    /// Returns the number of processed entries.
+    pub(crate) fn repair_aggregates_chunk(max_entries: u32) -> Result<u32, DispatchError> {
+        let accounts = <Accounts<T>>::final_prefix();
+        let reserves = <Reserves<T>>::final_prefix();
+        let mut state = <AggregatesRepair<T>>::get().unwrap_or_else(|| AggregatesRepairState {
+            last_key: accounts.to_vec(),
+            totals: Vec::new(),
+        });
+
+        let mut processed = 0;
+        while processed < max_entries {
+            let prefix = if state.last_key.starts_with(&accounts) {
+                accounts
+            } else {
+                reserves
+            };
+            let key = match sp_io::storage::next_key(&state.last_key)
+                .filter(|key| key.starts_with(&prefix))
+            {
+                Some(key) => key,
+                None if prefix == accounts => {
+                    state.last_key = reserves.to_vec();
+                    continue;
+                }
+                None => {
+                    <AggregatesRepair<T>>::kill();
+                    for (currency, stored, actual) in Self::aggregates_drift(state.totals) {
+                        <BalancesAggregates<T>>::insert(&currency, &actual);
+                        Self::deposit_event(RawEvent::AggregatesRepaired(currency, stored, actual));
+                    }
+                    return Ok(processed);
+                }
+            };
+
+            if prefix == accounts {
+                let account: AccountData<T::Balance> =
+                    unhashed::get(&key).unwrap_or_else(Default::default);
+                for (currency, balance) in account.balances() {
+                    Self::add_to_totals(&mut state.totals, currency, balance)?;
+                }
+            } else {
+                match Self::decode_reserves_key(&key[prefix.len()..]) {
+                    Some(currency) => {
+                        let entries: Vec<ReserveData<T::Balance>> =
+                            unhashed::get(&key).unwrap_or_else(Vec::new);
+                        for reserve in entries.iter() {
+                            Self::add_to_totals(
+                                &mut state.totals,
+                                &currency,
+                                &Positive(reserve.amount),
+                            )?;
+                        }
+                    }
+                    None => eq_warn!(
+                        target: LOG_TARGET,
+                        "skipped undecodable reserves key {:?}",
+                        key
+                    ),
+                }
+            }
+            state.last_key = key;
+            processed += 1;
+        }
+        <AggregatesRepair<T>>::put(state);
+        Ok(processed)
+    }
+
+    /// Currency of the `blake2_128_concat(who) ++ blake2_128_concat(currency)` key of
+    /// the `Reserves` double map
+    fn decode_reserves_key(key: &[u8]) -> Option<currency::Currency> {
+        let mut input = key.get(16..)?;
+        T::AccountId::decode(&mut input).ok()?;
+        let mut input = input.get(16..)?;
+        currency::Currency::decode(&mut input).ok()
+    }
+
+    /// Keeps totals of the running repair in sync with the entries it has already
+    /// processed, `key` is the storage key of the changed entry
+    pub(crate) fn track_repair(
+        key: &[u8],
+        currency: &currency::Currency,
+        old: &SignedBalance<T::Balance>,
+        new: &SignedBalance<T::Balance>,
+    ) {
+        if let Some(mut state) = <AggregatesRepair<T>>::get() {
+            let accounts = <Accounts<T>>::final_prefix();
+            let processed = if state.last_key.starts_with(&accounts) {
+                key.starts_with(&accounts) && key <= &state.last_key[..]
+            } else {
+                key.starts_with(&accounts) || key <= &state.last_key[..]
+            };
+            if !processed {
+                return;
+            }
+            let aggregate = aggregate_of(&mut state.totals, currency);
+            match aggregate.checked_sub(old).and_then(|a| a.checked_add(new)) {
+                Some(new_aggregate) => *aggregate = new_aggregate,
+                None => eq_error!(target: LOG_TARGET, "repair totals overflow for {:?}", currency),
+            }
+            <AggregatesRepair<T>>::put(state);
+        }
+    }
+
+    fn add_to_totals(
+        totals: &mut Totals<T::Balance>,
+        currency: &currency::Currency,
+        balance: &SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        let aggregate = aggregate_of(totals, currency);
+        *aggregate = aggregate.checked_add(balance).ok_or(Error::<T>::Overflow)?;
+        Ok(())
+    }
+}
@@ -1,23 +1,38 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use super::imbalances::{NegativeImbalance, PositiveImbalance};
-use frame_support::traits::{Currency, Get};
+use super::reserves::ReserveIdentifier;
+use frame_support::traits::{
+    BalanceStatus, Currency, Get, LockIdentifier, LockableCurrency, ReservableCurrency,
+    WithdrawReasons,
+};
+use sp_runtime::traits::Saturating;
 use sp_std::marker;
 
+/// Reserve identifier used by pallets that are not aware of named reserves
+pub const DEFAULT_RESERVE_ID: ReserveIdentifier = *b"eq/dflt ";
+
 /// Currency trait implementation based on data from CurrencyGetter trait
 pub struct BalanceAdapter<T, R, CurrencyGetter>(marker::PhantomData<(T, R, CurrencyGetter)>);
 
 impl<AccountId, T, R, CurrencyGetter> Currency<AccountId> for BalanceAdapter<T, R, CurrencyGetter>
 where
     T: super::Trait,
-    R: super::EqCurrency<AccountId, T::Balance>,
+    R: super::EqReservableCurrency<AccountId, T::Balance>,
     CurrencyGetter: Get<eq_primitives::currency::Currency>,
 {
     type Balance = T::Balance;
-    type PositiveImbalance = PositiveImbalance<T::Balance>;
-    type NegativeImbalance = NegativeImbalance<T::Balance>;
+    type PositiveImbalance = R::PositiveImbalance;
+    type NegativeImbalance = R::NegativeImbalance;
+    /// Positive balance together with the `DEFAULT_RESERVE_ID` reserve, same as
+    /// `reserved_balance`
     fn total_balance(who: &AccountId) -> Self::Balance {
-        R::total_balance(CurrencyGetter::get(), who)
+        let currency = CurrencyGetter::get();
+        R::total_balance(currency, who).saturating_add(R::reserved_balance_named(
+            currency,
+            &DEFAULT_RESERVE_ID,
+            who,
+        ))
     }
     fn can_slash(who: &AccountId, value: Self::Balance) -> bool {
         R::can_slash(CurrencyGetter::get(), who, value)
@@ -86,3 +101,77 @@ where
         R::make_free_balance_be(CurrencyGetter::get(), who, balance)
     }
 }
+
+impl<AccountId, T, R, CurrencyGetter> ReservableCurrency<AccountId>
+    for BalanceAdapter<T, R, CurrencyGetter>
+where
+    T: super::Trait,
+    R: super::EqReservableCurrency<AccountId, T::Balance>,
+    CurrencyGetter: Get<eq_primitives::currency::Currency>,
+{
+    fn can_reserve(who: &AccountId, value: Self::Balance) -> bool {
+        R::can_reserve(CurrencyGetter::get(), who, value)
+    }
+    fn slash_reserved(
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> (Self::NegativeImbalance, Self::Balance) {
+        R::slash_reserved_named(CurrencyGetter::get(), &DEFAULT_RESERVE_ID, who, value)
+    }
+    fn reserved_balance(who: &AccountId) -> Self::Balance {
+        R::reserved_balance_named(CurrencyGetter::get(), &DEFAULT_RESERVE_ID, who)
+    }
+    fn reserve(who: &AccountId, value: Self::Balance) -> sp_runtime::DispatchResult {
+        R::reserve_named(CurrencyGetter::get(), &DEFAULT_RESERVE_ID, who, value)
+    }
+    fn unreserve(who: &AccountId, value: Self::Balance) -> Self::Balance {
+        R::unreserve_named(CurrencyGetter::get(), &DEFAULT_RESERVE_ID, who, value)
+    }
+    fn repatriate_reserved(
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, sp_runtime::DispatchError> {
+        R::repatriate_reserved_named(
+            CurrencyGetter::get(),
+            &DEFAULT_RESERVE_ID,
+            slashed,
+            beneficiary,
+            value,
+            status,
+        )
+    }
+}
+
+impl<AccountId, T, R, CurrencyGetter> LockableCurrency<AccountId>
+    for BalanceAdapter<T, R, CurrencyGetter>
+where
+    T: super::Trait,
+    R: super::EqReservableCurrency<AccountId, T::Balance>
+        + super::EqLockableCurrency<AccountId, T::Balance>,
+    CurrencyGetter: Get<eq_primitives::currency::Currency>,
+{
+    type Moment = T::BlockNumber;
+    type MaxLocks = T::MaxLocks;
+
+    fn set_lock(
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        R::set_lock(CurrencyGetter::get(), id, who, amount, reasons)
+    }
+    fn extend_lock(
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        R::extend_lock(CurrencyGetter::get(), id, who, amount, reasons)
+    }
+    fn remove_lock(id: LockIdentifier, who: &AccountId) {
+        R::remove_lock(CurrencyGetter::get(), id, who)
+    }
+}
@@ -5,11 +5,25 @@ use super::*;
 use crate::Module as Balance;
 use eq_primitives::currency;
 use frame_benchmarking::{account, benchmarks};
+use frame_support::storage::migration::put_storage_value;
+use frame_support::storage::StoragePrefixedMap;
+use frame_support::{Blake2_128Concat, StorageHasher};
 use frame_system::{Module as System, RawOrigin};
+use sp_arithmetic::FixedPointNumber;
 use sp_runtime::traits::Bounded;
 
 const SEED: u32 = 0;
 
+/// Worst case of a balance change: a checkpoint was taken, so the old balance is
+/// recorded, and a running aggregates repair has already passed all accounts
+fn start_checkpoint_and_repair<T: Trait>() {
+    <Checkpoints<T>>::put(vec![System::<T>::block_number()]);
+    <AggregatesRepair<T>>::put(AggregatesRepairState {
+        last_key: <Reserves<T>>::final_prefix().to_vec(),
+        totals: Balance::<T>::compute_aggregates().unwrap_or_default(),
+    });
+}
+
 benchmarks! {
     _ { }
 
@@ -18,16 +32,130 @@ benchmarks! {
         let caller = account("caller", 0, SEED);
         Balance::<T>::make_free_balance_be(currency::Currency::Btc ,&caller, T::Balance::max_value());
         let to = account("to", 0, SEED);
+        start_checkpoint_and_repair::<T>();
     }: _ (RawOrigin::Signed(caller), currency::Currency::Btc, to, From::<u64>::from(1_000_000_000 as u64))
 
+    // every leg goes to a new account, listed currencies are cycled
+    transfer_many {
+        let t in 1 .. 100;
+        let caller = account("caller", 0, SEED);
+        let currencies = T::AssetGetter::currencies()
+            .into_iter()
+            .filter(|currency| *currency != currency::Currency::Usd)
+            .collect::<Vec<_>>();
+        for currency in currencies.iter() {
+            Balance::<T>::make_free_balance_be(*currency, &caller, T::Balance::max_value());
+        }
+        let transfers = currencies
+            .iter()
+            .cycle()
+            .take(t as usize)
+            .enumerate()
+            .map(|(i, currency)| (*currency, account("to", i as u32, SEED), From::<u64>::from(1_000_000_000 as u64)))
+            .collect::<Vec<_>>();
+        start_checkpoint_and_repair::<T>();
+    }: _ (RawOrigin::Signed(caller), transfers)
+
     deposit {
         let b in 0 .. 100;
         let to = account("to", 0, SEED);
+        start_checkpoint_and_repair::<T>();
     }: _ (RawOrigin::Root, currency::Currency::Btc, to, From::<u64>::from(1_000_000_000 as u64))
 
     burn {
         let b in 0 .. 100;
         let from = account("from", 0, SEED);
         Balance::<T>::make_free_balance_be(currency::Currency::Btc ,&from, From::<u64>::from(1_000_000_000 as u64));
+        start_checkpoint_and_repair::<T>();
     }: _ (RawOrigin::Root, currency::Currency::Btc, from, From::<u64>::from(1_000_000_000 as u64))
+
+    set_collateral_ratio {
+        let b in 0 .. 100;
+    }: _ (RawOrigin::Root, currency::Currency::Btc, Some(FixedU128::saturating_from_rational(3, 2)))
+
+    force_transfer {
+        let b in 0 .. 100;
+        let from: T::AccountId = account("from", 0, SEED);
+        Balance::<T>::make_free_balance_be(currency::Currency::Btc, &from, From::<u64>::from(1_000_000_000 as u64));
+        let to = account("to", 0, SEED);
+        start_checkpoint_and_repair::<T>();
+    }: _ (RawOrigin::Root, currency::Currency::Btc, from, to, From::<u64>::from(2_000_000_000 as u64))
+
+    force_set_balance {
+        let b in 0 .. 100;
+        let who: T::AccountId = account("who", 0, SEED);
+        Balance::<T>::make_free_balance_be(currency::Currency::Btc, &who, From::<u64>::from(1_000_000_000 as u64));
+        start_checkpoint_and_repair::<T>();
+    }: _ (RawOrigin::Root, currency::Currency::Btc, who, SignedBalance::Negative(From::<u64>::from(1_000_000_000 as u64)))
+
+    freeze {
+        let b in 0 .. 100;
+        let who: T::AccountId = account("who", 0, SEED);
+        let origin = T::FreezeOrigin::successful_origin();
+    }: _ (origin, who, Some(currency::Currency::Btc))
+
+    thaw {
+        let b in 0 .. 100;
+        let who: T::AccountId = account("who", 0, SEED);
+        <Freezes<T>>::insert(&who, Some(currency::Currency::Btc), true);
+        let origin = T::FreezeOrigin::successful_origin();
+    }: _ (origin, who, Some(currency::Currency::Btc))
+
+    checkpoint {
+        let b in 0 .. 100;
+        System::<T>::set_block_number(From::<u32>::from(10 as u32));
+    }: _ (RawOrigin::Root)
+
+    // all balances of the account are read with a single storage read
+    balances_of {
+        let c in 1 .. 5;
+        let who: T::AccountId = account("who", 0, SEED);
+        for currency in T::AssetGetter::currencies().into_iter().take(c as usize) {
+            Balance::<T>::make_free_balance_be(currency, &who, From::<u64>::from(1_000_000_000 as u64));
+        }
+    }: {
+        Balance::<T>::balances_of(&who);
+    }
+
+    // every old `Account` entry is drained into a new account, which takes a system reference
+    migrate_accounts {
+        let a in 1 .. 1000;
+        for i in 0 .. a {
+            let who: T::AccountId = account("who", i, SEED);
+            let mut key = Blake2_128Concat::hash(&who.encode());
+            key.extend(Blake2_128Concat::hash(&currency::Currency::Btc.encode()));
+            put_storage_value(
+                b"EqBalances",
+                b"Account",
+                &key,
+                SignedBalance::Positive(T::Balance::from(1_000_000_000 as u64)),
+            );
+        }
+    }: {
+        Balance::<T>::migrate_to_single_entry();
+    }
+    verify {
+        let who: T::AccountId = account("who", 0, SEED);
+        assert_eq!(
+            Balance::<T>::balances_of(&who),
+            vec![(currency::Currency::Btc, SignedBalance::Positive(T::Balance::from(1_000_000_000 as u64)))]
+        );
+        assert_eq!(System::<T>::refs(&who), 1);
+    }
+
+    // every account has balances in all listed currencies and no reserves, aggregates are repaired
+    // in one call
+    repair_aggregates {
+        let a in 1 .. 1000;
+        for i in 0 .. a {
+            let who: T::AccountId = account("who", i, SEED);
+            for currency in T::AssetGetter::currencies() {
+                Balance::<T>::make_free_balance_be(currency, &who, From::<u64>::from(1_000_000_000 as u64));
+            }
+        }
+        <BalancesAggregates<T>>::remove(currency::Currency::Btc);
+    }: _ (RawOrigin::Root, a + 1)
+    verify {
+        assert!(Balance::<T>::audit_aggregates().is_empty());
+    }
 }
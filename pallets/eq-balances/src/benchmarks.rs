@@ -8,6 +8,14 @@ impl crate::WeightInfo for () {
             .saturating_add(DbWeight::get().writes(5 as Weight))
             .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
     }
+    fn transfer_many(t: u32) -> Weight {
+        (31206000 as Weight)
+            .saturating_add((68417000 as Weight).saturating_mul(t as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((3 as Weight).saturating_mul(t as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(t as Weight)))
+    }
     fn deposit(b: u32) -> Weight {
         (42443000 as Weight)
             .saturating_add((0 as Weight).saturating_mul(b as Weight))
@@ -24,4 +32,62 @@ impl crate::WeightInfo for () {
             .saturating_add(DbWeight::get().writes(2 as Weight))
             .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
     }
+    fn set_collateral_ratio(b: u32) -> Weight {
+        (16480000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
+    fn force_transfer(b: u32) -> Weight {
+        (71924000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(4 as Weight))
+            .saturating_add(DbWeight::get().reads((0 as Weight).saturating_mul(b as Weight)))
+            .saturating_add(DbWeight::get().writes(4 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
+    fn force_set_balance(b: u32) -> Weight {
+        (40127000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(2 as Weight))
+            .saturating_add(DbWeight::get().reads((0 as Weight).saturating_mul(b as Weight)))
+            .saturating_add(DbWeight::get().writes(2 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
+    fn repair_aggregates(a: u32) -> Weight {
+        (24719000 as Weight)
+            .saturating_add((61384000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(DbWeight::get().reads(3 as Weight))
+            .saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(DbWeight::get().writes(6 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(a as Weight)))
+    }
+    fn migrate_accounts(a: u32) -> Weight {
+        (10344000 as Weight)
+            .saturating_add((38215000 as Weight).saturating_mul(a as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().reads((5 as Weight).saturating_mul(a as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((3 as Weight).saturating_mul(a as Weight)))
+    }
+    fn freeze(b: u32) -> Weight {
+        (17035000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
+    fn thaw(b: u32) -> Weight {
+        (16522000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
+    fn checkpoint(b: u32) -> Weight {
+        (18944000 as Weight)
+            .saturating_add((0 as Weight).saturating_mul(b as Weight))
+            .saturating_add(DbWeight::get().reads(1 as Weight))
+            .saturating_add(DbWeight::get().reads((0 as Weight).saturating_mul(b as Weight)))
+            .saturating_add(DbWeight::get().writes(1 as Weight))
+            .saturating_add(DbWeight::get().writes((0 as Weight).saturating_mul(b as Weight)))
+    }
 }
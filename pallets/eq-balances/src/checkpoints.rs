@@ -0,0 +1,130 @@
+use super::*;
+use frame_support::storage::{unhashed, StoragePrefixedMap};
+use sp_runtime::traits::Saturating;
+
+/// Balance of the account as of the checkpoint block
+pub type BalanceCheckpoint<BlockNumber, Balance> = (BlockNumber, SignedBalance<Balance>);
+
+impl<T: Trait> Module<T> {
+    /// Balance of the account in the currency as of the latest retained checkpoint at or
+    /// before `block`, `None` when there is no such checkpoint. Reserved funds are not
+    /// included.
+    pub fn balance_at(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        block: T::BlockNumber,
+    ) -> Option<SignedBalance<T::Balance>> {
+        let checkpoint = *Self::checkpoints().iter().rev().find(|c| **c <= block)?;
+        let recorded = <BalanceCheckpoints<T>>::get(who, currency)
+            .into_iter()
+            .find(|(block, _)| *block >= checkpoint)
+            .map(|(_, balance)| balance);
+        Some(recorded.unwrap_or_else(|| Self::account_balance(who, currency)))
+    }
+
+    /// Creates a checkpoint at the current block, balances are recorded lazily on their
+    /// first change after it
+    pub(crate) fn create_checkpoint(now: T::BlockNumber) -> DispatchResult {
+        <Checkpoints<T>>::try_mutate(|checkpoints| -> DispatchResult {
+            ensure!(
+                checkpoints.last() != Some(&now),
+                Error::<T>::CheckpointExists
+            );
+            checkpoints.push(now);
+            Ok(())
+        })?;
+        Self::deposit_event(RawEvent::CheckpointCreated(now));
+        Ok(())
+    }
+
+    /// Creates the periodic checkpoint and prunes checkpoints out of the retention window,
+    /// balances recorded for them are removed over the following blocks
+    pub(crate) fn on_checkpoint_block(now: T::BlockNumber) -> Weight {
+        let mut weight = T::DbWeight::get().reads(1);
+
+        let oldest = now.saturating_sub(T::CheckpointRetention::get());
+        let checkpoints = Self::checkpoints();
+        if checkpoints.first().map_or(false, |c| *c < oldest) {
+            <Checkpoints<T>>::put(
+                checkpoints
+                    .into_iter()
+                    .filter(|c| *c >= oldest)
+                    .collect::<Vec<_>>(),
+            );
+            CheckpointsPruning::mutate(|last_key| {
+                if last_key.is_none() {
+                    *last_key = Some(<BalanceCheckpoints<T>>::final_prefix().to_vec());
+                }
+            });
+            weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 2));
+        }
+        weight = weight.saturating_add(Self::prune_balance_checkpoints(
+            T::MaxCheckpointsPruned::get(),
+        ));
+
+        let period = T::CheckpointPeriod::get();
+        if !period.is_zero() && (now % period).is_zero() {
+            match Self::create_checkpoint(now) {
+                Ok(_) => weight = weight.saturating_add(T::WeightInfo::checkpoint(1)),
+                Err(e) => eq_warn!(target: LOG_TARGET, "checkpoint at {:?} skipped: {:?}", now, e),
+            }
+        }
+        weight
+    }
+
+    /// Removes balances of pruned checkpoints from up to `max_entries` entries of
+    /// `BalanceCheckpoints`, continuing where the previous block stopped
+    fn prune_balance_checkpoints(max_entries: u32) -> Weight {
+        let mut last_key = match CheckpointsPruning::get() {
+            Some(last_key) => last_key,
+            None => return T::DbWeight::get().reads(1),
+        };
+        let prefix = <BalanceCheckpoints<T>>::final_prefix();
+        let oldest = Self::checkpoints().first().copied();
+
+        let mut processed: Weight = 0;
+        while processed < max_entries as Weight {
+            let key =
+                match sp_io::storage::next_key(&last_key).filter(|key| key.starts_with(&prefix)) {
+                    Some(key) => key,
+                    None => {
+                        CheckpointsPruning::kill();
+                        return T::DbWeight::get().reads_writes(2 + 2 * processed, processed + 1);
+                    }
+                };
+            let mut entries: Vec<BalanceCheckpoint<T::BlockNumber, T::Balance>> =
+                unhashed::get(&key).unwrap_or_else(Vec::new);
+            let len = entries.len();
+            entries.retain(|(block, _)| oldest.map_or(false, |oldest| *block >= oldest));
+            if entries.is_empty() {
+                unhashed::kill(&key);
+            } else if entries.len() != len {
+                unhashed::put(&key, &entries);
+            }
+            last_key = key;
+            processed += 1;
+        }
+        CheckpointsPruning::put(last_key);
+        T::DbWeight::get().reads_writes(1 + 2 * processed, processed + 1)
+    }
+
+    /// Records the balance before its first change after the latest checkpoint, entries
+    /// of pruned checkpoints are removed
+    pub(crate) fn record_checkpoint(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        balance: &SignedBalance<T::Balance>,
+    ) {
+        let checkpoints = Self::checkpoints();
+        let (oldest, latest) = match (checkpoints.first(), checkpoints.last()) {
+            (Some(oldest), Some(latest)) => (*oldest, *latest),
+            _ => return,
+        };
+        <BalanceCheckpoints<T>>::mutate(who, currency, |entries| {
+            if entries.last().map_or(true, |(block, _)| *block < latest) {
+                entries.retain(|(block, _)| *block >= oldest);
+                entries.push((latest, balance.clone()));
+            }
+        });
+    }
+}
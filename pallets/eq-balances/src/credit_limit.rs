@@ -0,0 +1,75 @@
+use super::*;
+use sp_arithmetic::{FixedPointNumber, FixedU128};
+use sp_runtime::traits::UniqueSaturatedInto;
+use sp_std::marker::PhantomData;
+
+/// Source of currency prices used to value collateral and debt.
+pub trait PriceGetter {
+    /// Returns the price of one unit of `currency` in a common quote currency.
+    fn get_price(currency: &currency::Currency) -> Result<FixedU128, DispatchError>;
+}
+
+/// Balance checker that lets a currency go negative while the account's positive
+/// balances cover its debt.
+///
+/// The debt in every currency is valued with `P`, multiplied by the currency
+/// collateral ratio from `CollateralRatios` and compared with the value of all
+/// positive balances. Currencies without a collateral ratio can't be borrowed.
+pub struct CreditLimitChecker<T, P>(PhantomData<(T, P)>);
+
+impl<T: Trait, P: PriceGetter> BalanceChecker<T::Balance, T::AccountId>
+    for CreditLimitChecker<T, P>
+{
+    fn can_change_balance(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        change: &SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        let value = match change {
+            SignedBalance::Positive(_) => return Ok(()),
+            SignedBalance::Negative(value) => *value,
+        };
+
+        let mut balances = <Module<T>>::balances_of(who);
+        match balances.iter_mut().find(|(c, _)| c == currency) {
+            Some((_, balance)) => {
+                *balance = balance.sub_balance(value).ok_or(Error::<T>::Overflow)?;
+            }
+            None => balances.push((*currency, SignedBalance::Negative(value))),
+        }
+
+        let mut collateral: u128 = 0;
+        let mut required: u128 = 0;
+        for (currency, balance) in balances.iter() {
+            match balance {
+                SignedBalance::Positive(amount) => {
+                    if amount.is_zero() {
+                        continue;
+                    }
+                    let amount: u128 = (*amount).unique_saturated_into();
+                    let price = P::get_price(currency)?;
+                    collateral = collateral.saturating_add(price.saturating_mul_int(amount));
+                }
+                SignedBalance::Negative(debt) => {
+                    if debt.is_zero() {
+                        continue;
+                    }
+                    let ratio =
+                        CollateralRatios::get(currency).ok_or(Error::<T>::DebtNotAllowed)?;
+                    let debt: u128 = (*debt).unique_saturated_into();
+                    let price = P::get_price(currency)?;
+                    let debt_value = price.saturating_mul_int(debt);
+                    required = required.saturating_add(ratio.saturating_mul_int(debt_value));
+                }
+            }
+        }
+
+        ensure!(collateral >= required, Error::<T>::CreditLimitExceeded);
+        Ok(())
+    }
+}
+
+/// Checks that collateral ratio requires more collateral than the debt itself.
+pub(crate) fn is_valid_collateral_ratio(ratio: &FixedU128) -> bool {
+    *ratio >= FixedU128::saturating_from_integer(1)
+}
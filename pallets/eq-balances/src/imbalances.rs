@@ -2,226 +2,300 @@
 
 use super::*;
 use sp_std::mem;
+
 /// Opaque, move-only struct with private fields that serves as a token denoting that
-/// funds have been created without any equal and opposite accounting.
+/// funds of the currency have been created without any equal and opposite accounting.
+/// Total issuance of the currency is increased when it is dropped.
 #[must_use]
-pub struct PositiveImbalance<Balance>(Balance)
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default;
-
-impl<Balance> PositiveImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    /// Create a new positive imbalance from a balance.
-    pub fn new(amount: Balance) -> Self {
-        PositiveImbalance(amount)
+pub struct PositiveImbalance<T: Trait>(currency::Currency, T::Balance);
+
+impl<T: Trait> PositiveImbalance<T> {
+    /// Create a new positive imbalance of the currency from a balance.
+    pub fn new(currency: currency::Currency, amount: T::Balance) -> Self {
+        PositiveImbalance(currency, amount)
+    }
+
+    /// Currency of the imbalance, `Currency::Unknown` for imbalances created by `zero`
+    pub fn currency(&self) -> currency::Currency {
+        self.0
+    }
+
+    /// Merges imbalances of the same currency, returns both back otherwise
+    pub fn try_merge(self, other: Self) -> result::Result<Self, (Self, Self)> {
+        match merged_currency(&self.0, self.1, &other.0, other.1) {
+            Some(currency) => {
+                let amount = self.1.saturating_add(other.1);
+                mem::forget((self, other));
+                Ok(Self(currency, amount))
+            }
+            None => Err((self, other)),
+        }
+    }
+
+    /// Offsets imbalances of the same currency, returns both back otherwise
+    pub fn try_offset(
+        self,
+        other: NegativeImbalance<T>,
+    ) -> result::Result<result::Result<Self, NegativeImbalance<T>>, (Self, NegativeImbalance<T>)>
+    {
+        let currency = match merged_currency(&self.0, self.1, &other.0, other.1) {
+            Some(currency) => currency,
+            None => return Err((self, other)),
+        };
+        let (a, b) = (self.1, other.1);
+        mem::forget((self, other));
+
+        if a >= b {
+            Ok(Ok(Self(currency, a - b)))
+        } else {
+            Ok(Err(NegativeImbalance(currency, b - a)))
+        }
     }
 }
 
 /// Opaque, move-only struct with private fields that serves as a token denoting that
-/// funds have been destroyed without any equal and opposite accounting.
+/// funds of the currency have been destroyed without any equal and opposite accounting.
+/// Total issuance of the currency is decreased when it is dropped.
 #[must_use]
-pub struct NegativeImbalance<Balance>(Balance)
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default;
-
-impl<Balance> NegativeImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    /// Create a new negative imbalance from a balance.
-    pub fn new(amount: Balance) -> Self {
-        NegativeImbalance(amount)
+pub struct NegativeImbalance<T: Trait>(currency::Currency, T::Balance);
+
+impl<T: Trait> NegativeImbalance<T> {
+    /// Create a new negative imbalance of the currency from a balance.
+    pub fn new(currency: currency::Currency, amount: T::Balance) -> Self {
+        NegativeImbalance(currency, amount)
+    }
+
+    /// Currency of the imbalance, `Currency::Unknown` for imbalances created by `zero`
+    pub fn currency(&self) -> currency::Currency {
+        self.0
+    }
+
+    /// Merges imbalances of the same currency, returns both back otherwise
+    pub fn try_merge(self, other: Self) -> result::Result<Self, (Self, Self)> {
+        match merged_currency(&self.0, self.1, &other.0, other.1) {
+            Some(currency) => {
+                let amount = self.1.saturating_add(other.1);
+                mem::forget((self, other));
+                Ok(Self(currency, amount))
+            }
+            None => Err((self, other)),
+        }
+    }
+
+    /// Offsets imbalances of the same currency, returns both back otherwise
+    pub fn try_offset(
+        self,
+        other: PositiveImbalance<T>,
+    ) -> result::Result<result::Result<Self, PositiveImbalance<T>>, (Self, PositiveImbalance<T>)>
+    {
+        let currency = match merged_currency(&self.0, self.1, &other.0, other.1) {
+            Some(currency) => currency,
+            None => return Err((self, other)),
+        };
+        let (a, b) = (self.1, other.1);
+        mem::forget((self, other));
+
+        if a >= b {
+            Ok(Ok(Self(currency, a - b)))
+        } else {
+            Ok(Err(PositiveImbalance(currency, b - a)))
+        }
+    }
+}
+
+/// Currency of two combined imbalances. Zero imbalances take the currency of the other
+/// one, imbalances of different currencies can't be combined.
+fn merged_currency<Balance: Zero>(
+    a: &currency::Currency,
+    a_amount: Balance,
+    b: &currency::Currency,
+    b_amount: Balance,
+) -> Option<currency::Currency> {
+    if a == b || b_amount.is_zero() {
+        Some(*a)
+    } else if a_amount.is_zero() {
+        Some(*b)
+    } else {
+        None
     }
 }
 
-impl<Balance> TryDrop for PositiveImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
+impl<T: Trait> TryDrop for PositiveImbalance<T> {
     fn try_drop(self) -> result::Result<(), Self> {
         self.drop_zero()
     }
 }
 
-impl<Balance> Imbalance<Balance> for PositiveImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    type Opposite = NegativeImbalance<Balance>;
+/// Combining imbalances of different currencies is a bug, it fails in debug builds and
+/// leaves them to settle separately otherwise. Use `try_merge` and `try_offset` instead.
+impl<T: Trait> Imbalance<T::Balance> for PositiveImbalance<T> {
+    type Opposite = NegativeImbalance<T>;
 
     fn zero() -> Self {
-        Self(Zero::zero())
+        Self(currency::Currency::Unknown, Zero::zero())
     }
     fn drop_zero(self) -> result::Result<(), Self> {
-        if self.0.is_zero() {
+        if self.1.is_zero() {
+            mem::forget(self);
             Ok(())
         } else {
             Err(self)
         }
     }
-    fn split(self, amount: Balance) -> (Self, Self) {
-        let first = self.0.min(amount);
-        let second = self.0 - first;
+    fn split(self, amount: T::Balance) -> (Self, Self) {
+        let first = self.1.min(amount);
+        let second = self.1 - first;
+        let currency = self.0;
 
         mem::forget(self);
-        (Self(first), Self(second))
+        (Self(currency, first), Self(currency, second))
     }
-    fn merge(mut self, other: Self) -> Self {
-        self.0 = self.0.saturating_add(other.0);
-        mem::forget(other);
-
-        self
+    fn merge(self, other: Self) -> Self {
+        match self.try_merge(other) {
+            Ok(merged) => merged,
+            Err((this, other)) => {
+                eq_error!(
+                    target: LOG_TARGET,
+                    "merge of {:?} and {:?} imbalances",
+                    this.0,
+                    other.0
+                );
+                debug_assert_eq!(
+                    this.0, other.0,
+                    "merge of imbalances of different currencies"
+                );
+                this
+            }
+        }
     }
     fn subsume(&mut self, other: Self) {
-        self.0 = self.0.saturating_add(other.0);
-        mem::forget(other);
+        let this = mem::replace(self, Self::zero());
+        *self = this.merge(other);
     }
     fn offset(self, other: Self::Opposite) -> result::Result<Self, Self::Opposite> {
-        let (a, b) = (self.0, other.0);
-        mem::forget((self, other));
-
-        if a >= b {
-            Ok(Self(a - b))
-        } else {
-            Err(NegativeImbalance::new(b - a))
+        match self.try_offset(other) {
+            Ok(offset) => offset,
+            Err((this, other)) => {
+                eq_error!(
+                    target: LOG_TARGET,
+                    "offset of {:?} and {:?} imbalances",
+                    this.0,
+                    other.0
+                );
+                debug_assert_eq!(
+                    this.0, other.0,
+                    "offset of imbalances of different currencies"
+                );
+                Ok(this)
+            }
         }
     }
-    fn peek(&self) -> Balance {
-        self.0.clone()
+    fn peek(&self) -> T::Balance {
+        self.1
     }
 }
 
-impl<Balance> TryDrop for NegativeImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
+impl<T: Trait> TryDrop for NegativeImbalance<T> {
     fn try_drop(self) -> result::Result<(), Self> {
         self.drop_zero()
     }
 }
 
-impl<Balance> Imbalance<Balance> for NegativeImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    type Opposite = PositiveImbalance<Balance>;
+/// Combining imbalances of different currencies is a bug, it fails in debug builds and
+/// leaves them to settle separately otherwise. Use `try_merge` and `try_offset` instead.
+impl<T: Trait> Imbalance<T::Balance> for NegativeImbalance<T> {
+    type Opposite = PositiveImbalance<T>;
 
     fn zero() -> Self {
-        Self(Zero::zero())
+        Self(currency::Currency::Unknown, Zero::zero())
     }
     fn drop_zero(self) -> result::Result<(), Self> {
-        if self.0.is_zero() {
+        if self.1.is_zero() {
+            mem::forget(self);
             Ok(())
         } else {
             Err(self)
         }
     }
-    fn split(self, amount: Balance) -> (Self, Self) {
-        let first = self.0.min(amount);
-        let second = self.0 - first;
+    fn split(self, amount: T::Balance) -> (Self, Self) {
+        let first = self.1.min(amount);
+        let second = self.1 - first;
+        let currency = self.0;
 
         mem::forget(self);
-        (Self(first), Self(second))
+        (Self(currency, first), Self(currency, second))
     }
-    fn merge(mut self, other: Self) -> Self {
-        self.0 = self.0.saturating_add(other.0);
-        mem::forget(other);
-
-        self
+    fn merge(self, other: Self) -> Self {
+        match self.try_merge(other) {
+            Ok(merged) => merged,
+            Err((this, other)) => {
+                eq_error!(
+                    target: LOG_TARGET,
+                    "merge of {:?} and {:?} imbalances",
+                    this.0,
+                    other.0
+                );
+                debug_assert_eq!(
+                    this.0, other.0,
+                    "merge of imbalances of different currencies"
+                );
+                this
+            }
+        }
     }
     fn subsume(&mut self, other: Self) {
-        self.0 = self.0.saturating_add(other.0);
-        mem::forget(other);
+        let this = mem::replace(self, Self::zero());
+        *self = this.merge(other);
     }
     fn offset(self, other: Self::Opposite) -> result::Result<Self, Self::Opposite> {
-        let (a, b) = (self.0, other.0);
-        mem::forget((self, other));
-
-        if a >= b {
-            Ok(Self(a - b))
-        } else {
-            Err(PositiveImbalance::new(b - a))
+        match self.try_offset(other) {
+            Ok(offset) => offset,
+            Err((this, other)) => {
+                eq_error!(
+                    target: LOG_TARGET,
+                    "offset of {:?} and {:?} imbalances",
+                    this.0,
+                    other.0
+                );
+                debug_assert_eq!(
+                    this.0, other.0,
+                    "offset of imbalances of different currencies"
+                );
+                Ok(this)
+            }
         }
     }
-    fn peek(&self) -> Balance {
-        self.0.clone()
+    fn peek(&self) -> T::Balance {
+        self.1
     }
 }
 
-impl<Balance> Drop for PositiveImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    /// Basic drop handler will just square up the total issuance.
-    fn drop(&mut self) {}
+impl<T: Trait> Drop for PositiveImbalance<T> {
+    /// Settles the total issuance of the currency.
+    fn drop(&mut self) {
+        if self.1.is_zero() {
+            return;
+        }
+        <BalancesAggregates<T>>::mutate(&self.0, |aggregate| {
+            match aggregate.checked_add(&Positive(self.1)) {
+                Some(new_aggregate) => *aggregate = new_aggregate,
+                None => eq_error!(target: LOG_TARGET, "issuance overflow for {:?}", self.0),
+            }
+        });
+    }
 }
 
-impl<Balance> Drop for NegativeImbalance<Balance>
-where
-    Balance: Member
-        + AtLeast32BitUnsigned
-        + FullCodec
-        + Copy
-        + MaybeSerializeDeserialize
-        + Debug
-        + Default,
-{
-    /// Basic drop handler will just square up the total issuance.
-    fn drop(&mut self) {}
+impl<T: Trait> Drop for NegativeImbalance<T> {
+    /// Settles the total issuance of the currency.
+    fn drop(&mut self) {
+        if self.1.is_zero() {
+            return;
+        }
+        <BalancesAggregates<T>>::mutate(&self.0, |aggregate| {
+            match aggregate.checked_sub(&Positive(self.1)) {
+                Some(new_aggregate) => *aggregate = new_aggregate,
+                None => eq_error!(target: LOG_TARGET, "issuance underflow for {:?}", self.0),
+            }
+        });
+    }
 }
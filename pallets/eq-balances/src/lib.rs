@@ -1,43 +1,77 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+pub mod account_data;
+pub mod aggregates;
 pub mod balance_adapter;
 mod benchmarking;
 mod benchmarks;
+pub mod checkpoints;
+pub mod credit_limit;
 mod imbalances;
+pub mod locks;
 mod mock;
+pub mod multi_currency;
+pub mod reserves;
 pub mod signed_balance;
 mod tests;
 
+pub use account_data::AccountData;
+pub use aggregates::AggregatesRepairState;
+pub use checkpoints::BalanceCheckpoint;
 use codec::{Codec, Decode, Encode, FullCodec};
+pub use credit_limit::{CreditLimitChecker, PriceGetter};
+use eq_primitives::asset::{AssetGetter, AssetStatus};
 pub use eq_primitives::currency;
 use eq_primitives::currency::Currency;
+use eq_primitives::AccountGetter;
+use eq_utils::log::{eq_error, eq_warn};
 use frame_support::traits::{
-    ExistenceRequirement, Get, Imbalance, OnKilledAccount, SignedImbalance, TryDrop,
-    WithdrawReasons,
+    BalanceStatus, EnsureOrigin, ExistenceRequirement, Get, Happened, Imbalance, OnKilledAccount,
+    SignedImbalance, TryDrop, WithdrawReason, WithdrawReasons,
 };
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
-    storage::IterableStorageDoubleMap,
+    storage::{
+        migration::StorageIterator, with_transaction, IterableStorageDoubleMap, IterableStorageMap,
+    },
     weights::Weight,
     Parameter,
 };
 pub use imbalances::{NegativeImbalance, PositiveImbalance};
 use impl_trait_for_tuples::impl_for_tuples;
+pub use locks::{BalanceLock, EqLockableCurrency, Reasons};
+pub use reserves::{EqReservableCurrency, ReserveData, ReserveIdentifier};
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 pub use signed_balance::{SignedBalance, SignedBalance::*};
-use sp_runtime::traits::{AtLeast32BitUnsigned, MaybeSerializeDeserialize, Member, Zero};
+use sp_arithmetic::FixedU128;
+use sp_runtime::traits::{
+    AtLeast32Bit, AtLeast32BitUnsigned, Bounded, MaybeSerializeDeserialize, Member, Signed, Zero,
+};
+use sp_runtime::{RuntimeDebug, TransactionOutcome};
+use sp_std::convert::{TryFrom, TryInto};
 use sp_std::prelude::*;
 use sp_std::{fmt::Debug, result};
 use system as frame_system;
 use system::{ensure_root, ensure_signed};
 
+const LOG_TARGET: &str = "eq_balances";
+
 pub trait WeightInfo {
     fn transfer(b: u32) -> Weight;
+    fn transfer_many(t: u32) -> Weight;
     fn deposit(b: u32) -> Weight;
     fn burn(b: u32) -> Weight;
+    fn set_collateral_ratio(b: u32) -> Weight;
+    fn force_transfer(b: u32) -> Weight;
+    fn force_set_balance(b: u32) -> Weight;
+    fn migrate_accounts(a: u32) -> Weight;
+    fn repair_aggregates(a: u32) -> Weight;
+    fn freeze(b: u32) -> Weight;
+    fn thaw(b: u32) -> Weight;
+    fn checkpoint(b: u32) -> Weight;
 }
 
 pub trait Trait: system::Trait {
@@ -52,40 +86,136 @@ pub trait Trait: system::Trait {
         + Debug
         + From<u64>
         + Into<u64>;
+    /// Signed balance change used by `MultiCurrencyExtended::update_balance`
+    type Amount: Signed
+        + TryInto<Self::Balance>
+        + TryFrom<Self::Balance>
+        + Parameter
+        + Member
+        + AtLeast32Bit
+        + Default
+        + Copy
+        + MaybeSerializeDeserialize;
     type TotalIssuance: Get<Self::Balance>; // change for multi currency
+    /// Minimum balance for currencies missing from the asset registry
     type ExistentialDeposit: Get<Self::Balance>;
+    /// Account that collects dust of reaped balances
+    type TreasuryAccountGetter: AccountGetter<Self::AccountId>;
+    /// Maximum number of named reserves per account and currency
+    type MaxReserves: Get<u32>;
+    /// Expected maximum number of locks per account and currency, exceeding it only
+    /// produces a warning
+    type MaxLocks: Get<u32>;
+    /// Origin that freezes and thaws accounts
+    type FreezeOrigin: EnsureOrigin<Self::Origin>;
+    /// Blocks between automatic balance checkpoints, zero disables them
+    type CheckpointPeriod: Get<Self::BlockNumber>;
+    /// Checkpoints older than this number of blocks are pruned
+    type CheckpointRetention: Get<Self::BlockNumber>;
+    /// Maximum number of `BalanceCheckpoints` entries checked for pruning per block
+    type MaxCheckpointsPruned: Get<u32>;
 
     type BalanceChecker: BalanceChecker<Self::Balance, Self::AccountId>;
     type BalanceGetter: BalanceGetter<Self::AccountId, Self::Balance>;
+    /// Registry of listed currencies, the only source of per-currency existential deposits
+    type AssetGetter: AssetGetter<Self::Balance>;
 
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
     type WeightInfo: WeightInfo;
 }
 
 /// Stores total values of issuance and debt.
-#[derive(Encode, Decode, Clone, Default, PartialEq, Debug)]
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct BalancesAggregate<Balance> {
     pub total_issuance: Balance,
     pub total_debt: Balance,
 }
 
+/// Account, currency, balance before and balance after a staged change
+type BalanceChange<T> = (
+    <T as system::Trait>::AccountId,
+    currency::Currency,
+    SignedBalance<<T as Trait>::Balance>,
+    SignedBalance<<T as Trait>::Balance>,
+);
+
+/// Storage layout versions of the module
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    /// Balances are stored in `Account` double map keyed by account and currency
+    V1DoubleMap,
+    /// All balances of the account are stored in a single `Accounts` entry
+    V2SingleEntry,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1DoubleMap
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as EqBalances {
-        pub Account: double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) currency::Currency => SignedBalance<T::Balance>;
+        /// Balances of the account in all currencies
+        pub Accounts get(fn accounts): map hasher(blake2_128_concat) T::AccountId => AccountData<T::Balance>;
 
         pub BalancesAggregates get (fn balances_aggregates): map hasher(blake2_128_concat) currency::Currency => BalancesAggregate<T::Balance>;
+
+        /// Named reserves of the account, reserved funds are not part of `Account` balance
+        pub Reserves get(fn reserves): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) currency::Currency => Vec<ReserveData<T::Balance>>;
+
+        /// Locks keeping part of the account balance from being withdrawn
+        pub Locks get(fn locks): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) currency::Currency => Vec<BalanceLock<T::Balance>>;
+
+        /// Collateral required per unit of debt in the currency, the currency can't go
+        /// negative when not set
+        pub CollateralRatios get(fn collateral_ratios): map hasher(blake2_128_concat) currency::Currency => Option<FixedU128>;
+
+        /// Accounts that can't move funds, per currency or in all currencies when the
+        /// currency is `None`
+        pub Freezes get(fn freezes): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) Option<currency::Currency> => bool;
+
+        /// Blocks of the retained balance checkpoints in ascending order
+        pub Checkpoints get(fn checkpoints): Vec<T::BlockNumber>;
+
+        /// Balances of the account as of checkpoints, recorded on the first change after
+        /// each checkpoint
+        pub BalanceCheckpoints get(fn balance_checkpoints): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) currency::Currency => Vec<BalanceCheckpoint<T::BlockNumber, T::Balance>>;
+
+        /// Storage key of the last `BalanceCheckpoints` entry checked by the running
+        /// pruning of removed checkpoints
+        pub CheckpointsPruning get(fn checkpoints_pruning): Option<Vec<u8>>;
+
+        /// Progress of the running `repair_aggregates`
+        pub AggregatesRepair get(fn aggregates_repair): Option<AggregatesRepairState<T::Balance>>;
+
+        /// Storage layout version, new chains start with single entry accounts
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V2SingleEntry): Releases;
     }
     add_extra_genesis {
-        config(balances): Vec<(T::AccountId, T::Balance, u8)>;
-        // ^^ begin, length, amount liquid at genesis
+        /// Balances at genesis, negative ones start as debt
+        config(balances): Vec<(T::AccountId, currency::Currency, SignedBalance<T::Balance>)>;
         build(|config: &GenesisConfig<T>| {
-
-            for &(ref who, free, currency) in config.balances.iter() {
-                let currency_typed: currency::Currency = currency.into();
-                <Account<T>>::insert(who, currency_typed, SignedBalance::Positive(free));
+            let mut written: Vec<(&T::AccountId, &currency::Currency)> = Vec::new();
+            for (who, currency, balance) in config.balances.iter() {
+                assert!(
+                    *currency != currency::Currency::Unknown,
+                    "Unknown currency in genesis balances"
+                );
+                assert!(
+                    !written.contains(&(who, currency)),
+                    "Duplicate genesis balance of the account in the currency"
+                );
+                written.push((who, currency));
+                <Module<T>>::write_balance(who, currency, balance.clone());
+            }
+            // currencies come from the balances, the asset registry may be built later
+            let totals = <Module<T>>::compute_aggregates()
+                .expect("Genesis balances overflow the aggregates");
+            for (currency, aggregate) in totals.iter() {
+                <BalancesAggregates<T>>::insert(currency, aggregate);
             }
-            <Module<T>>::balances_aggregates_fix();
         });
     }
 }
@@ -94,9 +224,49 @@ decl_storage! {
 decl_event!(
     pub enum Event<T> where
         <T as system::Trait>::AccountId,
-        <T as Trait>::Balance
+        <T as Trait>::Balance,
+        <T as system::Trait>::BlockNumber
     {
         Transfer(AccountId, AccountId, Currency, Balance),
+        /// Some amount was added to the account balance
+        Deposit(AccountId, Currency, Balance),
+        /// Some amount was withdrawn from the account balance
+        Withdraw(AccountId, Currency, Balance),
+        /// Account balance was forcibly set to the value
+        BalanceSet(AccountId, Currency, SignedBalance<Balance>),
+        /// Positive balance of the account became negative, carries the new debt
+        DebtCreated(AccountId, Currency, Balance),
+        /// Negative balance of the account became positive, carries the repaid debt
+        DebtRepaid(AccountId, Currency, Balance),
+        /// Balance below existential deposit was removed from the account, carries the
+        /// dust moved to the treasury
+        AccountReaped(AccountId, Currency, Balance),
+        /// Some amount was removed from the account by slashing
+        Slashed(AccountId, Currency, Balance),
+        /// Total issuance of the currency was increased by the root `deposit` call
+        Issued(Currency, Balance),
+        /// Total issuance of the currency was decreased by the root `burn` call
+        Burned(Currency, Balance),
+        /// Collateral ratio of the currency was changed, `None` disables borrowing it
+        CollateralRatioSet(Currency, Option<FixedU128>),
+        /// Some amount was moved from the account balance to the named reserve
+        Reserved(AccountId, Currency, ReserveIdentifier, Balance),
+        /// Some amount was moved from the named reserve back to the account balance
+        Unreserved(AccountId, Currency, ReserveIdentifier, Balance),
+        /// Some amount was removed from the named reserve by slashing
+        ReserveSlashed(AccountId, Currency, ReserveIdentifier, Balance),
+        /// Reserved amount was moved to another account, either to its balance or to its
+        /// reserve with the same identifier
+        ReserveRepatriated(AccountId, AccountId, Currency, ReserveIdentifier, Balance, BalanceStatus),
+        /// Aggregates of the currency didn't match account balances and were repaired,
+        /// carries stored and actual aggregates
+        AggregatesRepaired(Currency, BalancesAggregate<Balance>, BalancesAggregate<Balance>),
+        /// Account can't move funds of the currency, `None` stands for all currencies
+        Frozen(AccountId, Option<Currency>),
+        /// Account frozen for the currency can move funds again
+        Thawed(AccountId, Option<Currency>),
+        /// Balances of all accounts were checkpointed at the block
+        CheckpointCreated(BlockNumber),
     }
 );
 
@@ -119,9 +289,28 @@ decl_error! {
         ExistingVestingSchedule,
         /// Beneficiary account must pre-exist
         DeadAccount,
-
+        /// Balance checker rejected the change without a more specific reason
         NotAllowedToChangeBalance,
-
+        /// Number of named reserves exceeds `MaxReserves`
+        TooManyReserves,
+        /// Currency can't go negative because it has no collateral ratio
+        DebtNotAllowed,
+        /// Account collateral doesn't cover its debt
+        CreditLimitExceeded,
+        /// Collateral ratio must be at least one
+        InvalidCollateralRatio,
+        /// Aggregates are lower than the balance removed from them, see `repair_aggregates`
+        AggregatesUnderflow,
+        /// Account is frozen and can't move funds of the currency
+        AccountFrozen,
+        /// Checkpoint at the current block already exists
+        CheckpointExists,
+        /// Amount can't be converted to balance
+        AmountIntoBalanceFailed,
+        /// Currency is frozen or delisted in the asset registry
+        CurrencyNotActive,
+        /// `Currency::Unknown` can't hold balances
+        UnknownCurrency,
     }
 }
 
@@ -132,6 +321,10 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_initialize(n: T::BlockNumber) -> Weight {
+            Self::on_checkpoint_block(n)
+        }
+
         /// Performs transfer to the specified account
         // #[weight = 10_000]
         #[weight = T::WeightInfo::transfer(1)]
@@ -141,18 +334,32 @@ decl_module! {
             Self::currency_transfer(currency, &from, &to, value, ExistenceRequirement::AllowDeath)
         }
 
+        /// Performs several transfers in any currencies, either all of them or none
+        #[weight = T::WeightInfo::transfer_many(transfers.len() as u32)]
+        pub fn transfer_many(origin, transfers: Vec<(currency::Currency, <T as system::Trait>::AccountId, T::Balance)>) -> DispatchResult
+        {
+            let from = ensure_signed(origin)?;
+            Self::currency_transfer_many(&from, &transfers)
+        }
+
         /// Performs deposit to the specified account
         // #[weight = 10_000]
         #[weight = T::WeightInfo::deposit(1)]
         pub fn deposit(origin, currency: currency::Currency, to: <T as system::Trait>::AccountId, value: T::Balance) -> DispatchResult
         {
             ensure_root(origin)?;
+            Self::ensure_currency_active(&currency)?;
 
-            #[allow(unused_must_use)]
-            if <Account<T>>::contains_key(&to, &currency) {
-                Self::deposit_into_existing(currency, &to, value)?;
+            let imbalance = if !Self::account_balance(&to, &currency).is_zero() {
+                Self::deposit_into_existing(currency, &to, value)?
             } else {
-                Self::deposit_creating(currency, &to, value);
+                Self::deposit_creating(currency, &to, value)
+            };
+            // issuance is settled when the imbalance is dropped
+            let issued = imbalance.peek();
+            drop(imbalance);
+            if !issued.is_zero() {
+                Self::deposit_event(RawEvent::Issued(currency, issued));
             }
             Ok(())
         }
@@ -164,18 +371,131 @@ decl_module! {
         {
             ensure_root(origin)?;
 
-            #[allow(unused_must_use)] {
-                Self::withdraw(
-                    currency,
-                    &from,
-                    value,
-                    WithdrawReasons::all(),
-                    ExistenceRequirement::AllowDeath
-                )?;
+            let imbalance = Self::withdraw(
+                currency,
+                &from,
+                value,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath
+            )?;
+            // issuance is settled when the imbalance is dropped
+            let burned = imbalance.peek();
+            drop(imbalance);
+            if !burned.is_zero() {
+                Self::deposit_event(RawEvent::Burned(currency, burned));
+            }
+            Ok(())
+        }
+
+        /// Sets collateral required per unit of debt in the currency, `None` disables borrowing it
+        #[weight = T::WeightInfo::set_collateral_ratio(1)]
+        pub fn set_collateral_ratio(origin, currency: currency::Currency, ratio: Option<FixedU128>) -> DispatchResult
+        {
+            ensure_root(origin)?;
+            if let Some(ratio) = ratio {
+                ensure!(credit_limit::is_valid_collateral_ratio(&ratio), Error::<T>::InvalidCollateralRatio);
+            }
+
+            CollateralRatios::mutate(&currency, |r| *r = ratio);
+            Self::deposit_event(RawEvent::CollateralRatioSet(currency, ratio));
+            Ok(())
+        }
+
+        /// Moves funds between accounts bypassing balance checker and locks, the source
+        /// balance may become negative. Existential deposit rules are the same as for
+        /// `transfer`: dust left on the source is reaped.
+        #[weight = T::WeightInfo::force_transfer(1)]
+        pub fn force_transfer(origin, currency: currency::Currency, from: <T as system::Trait>::AccountId, to: <T as system::Trait>::AccountId, value: T::Balance) -> DispatchResult
+        {
+            ensure_root(origin)?;
+            ensure!(currency != currency::Currency::Unknown, Error::<T>::UnknownCurrency);
+            if value.is_zero() || from == to {
+                return Ok(());
             }
 
+            let from_balance = Self::account_balance(&from, &currency);
+            let to_balance = Self::account_balance(&to, &currency);
+            let new_from_balance = from_balance.sub_balance(value).ok_or(Error::<T>::Overflow)?;
+            let new_to_balance = to_balance.add_balance(value).ok_or(Error::<T>::Overflow)?;
+
+            let dust = Self::dust_of(currency, &new_from_balance, ExistenceRequirement::AllowDeath)?;
+            ensure!(
+                !to_balance.is_zero()
+                    || Self::free_balance_of(&new_to_balance) >= Self::currency_minimum_balance(currency),
+                Error::<T>::ExistentialDeposit
+            );
+
+            Self::update_balances(&currency, vec![
+                (&from, from_balance, new_from_balance),
+                (&to, to_balance, new_to_balance),
+            ])?;
+            Self::reap_dust(currency, &from, dust);
+            Self::deposit_event(RawEvent::Transfer(from, to, currency, value));
+            Ok(())
+        }
+
+        /// Sets the account balance to the value, positive or negative. Positive balance
+        /// can't be below existential deposit, set zero to remove it.
+        #[weight = T::WeightInfo::force_set_balance(1)]
+        pub fn force_set_balance(origin, currency: currency::Currency, who: <T as system::Trait>::AccountId, value: SignedBalance<T::Balance>) -> DispatchResult
+        {
+            ensure_root(origin)?;
+            ensure!(currency != currency::Currency::Unknown, Error::<T>::UnknownCurrency);
+            let free = Self::free_balance_of(&value);
+            ensure!(
+                free.is_zero() || free >= Self::currency_minimum_balance(currency),
+                Error::<T>::ExistentialDeposit
+            );
+
+            let balance = Self::account_balance(&who, &currency);
+            Self::update_balance(&who, &currency, &balance, value.clone())?;
+            Self::deposit_event(RawEvent::BalanceSet(who, currency, value));
+            Ok(())
+        }
+
+        /// Recomputes `BalancesAggregates` from up to `max_entries` entries of `Accounts`
+        /// and `Reserves`, continuing where the previous call stopped. Drifted aggregates
+        /// are reported and repaired after the last entry.
+        #[weight = T::WeightInfo::repair_aggregates(*max_entries)]
+        pub fn repair_aggregates(origin, max_entries: u32) -> DispatchResult
+        {
+            ensure_root(origin)?;
+
+            Self::repair_aggregates_chunk(max_entries)?;
             Ok(())
         }
+
+        /// Stops the account from moving funds of the currency, or of all currencies when
+        /// `currency` is `None`. Balances are left untouched.
+        #[weight = T::WeightInfo::freeze(1)]
+        pub fn freeze(origin, who: <T as system::Trait>::AccountId, currency: Option<currency::Currency>) -> DispatchResult
+        {
+            T::FreezeOrigin::ensure_origin(origin)?;
+
+            <Freezes<T>>::insert(&who, &currency, true);
+            Self::deposit_event(RawEvent::Frozen(who, currency));
+            Ok(())
+        }
+
+        /// Removes the freeze set by `freeze` with the same currency
+        #[weight = T::WeightInfo::thaw(1)]
+        pub fn thaw(origin, who: <T as system::Trait>::AccountId, currency: Option<currency::Currency>) -> DispatchResult
+        {
+            T::FreezeOrigin::ensure_origin(origin)?;
+
+            <Freezes<T>>::remove(&who, &currency);
+            Self::deposit_event(RawEvent::Thawed(who, currency));
+            Ok(())
+        }
+
+        /// Checkpoints balances of all accounts at the current block
+        #[weight = T::WeightInfo::checkpoint(1)]
+        pub fn checkpoint(origin) -> DispatchResult
+        {
+            ensure_root(origin)?;
+
+            Self::create_checkpoint(<system::Module<T>>::block_number())
+        }
     }
 }
 
@@ -188,7 +508,7 @@ where
 
 impl<T: Trait> BalanceGetter<T::AccountId, T::Balance> for Module<T> {
     fn get_balance(who: &T::AccountId, currency: &currency::Currency) -> SignedBalance<T::Balance> {
-        <Account<T>>::get(who, currency)
+        Self::account_balance(who, currency)
     }
 }
 /// Contains several operations to modify balances
@@ -216,13 +536,18 @@ where
 
     fn remove_balance_unsafe(who: &AccountId, currency: &currency::Currency);
 
+    /// Sets the balance keeping aggregates consistent, fails when they would overflow
     fn set_balance_with_agg_unsafe(
         who: &AccountId,
         currency: &currency::Currency,
         value: SignedBalance<Balance>,
-    );
+    ) -> DispatchResult;
 
-    fn remove_balance_with_agg_unsafe(who: &AccountId, currency: &currency::Currency);
+    /// Removes the balance keeping aggregates consistent
+    fn remove_balance_with_agg_unsafe(
+        who: &AccountId,
+        currency: &currency::Currency,
+    ) -> DispatchResult;
 }
 
 impl<T: Trait> BalanceSetter<T::AccountId, T::Balance> for Module<T> {
@@ -231,9 +556,8 @@ impl<T: Trait> BalanceSetter<T::AccountId, T::Balance> for Module<T> {
         currency: &currency::Currency,
         value: SignedBalance<T::Balance>,
     ) {
-        <Account<T>>::mutate(who, currency, |balance| {
-            *balance = value;
-        });
+        Self::write_balance(who, currency, value.clone());
+        Self::deposit_event(RawEvent::BalanceSet(who.clone(), *currency, value));
     }
 
     fn add_balance_unsafe(
@@ -241,14 +565,13 @@ impl<T: Trait> BalanceSetter<T::AccountId, T::Balance> for Module<T> {
         currency: &currency::Currency,
         value: &SignedBalance<T::Balance>,
     ) {
-        <Account<T>>::mutate(who, currency, |balance| match value {
-            Positive(p) => {
-                *balance = balance.add_balance(*p).unwrap();
-            }
-            Negative(n) => {
-                *balance = balance.sub_balance(*n).unwrap();
-            }
-        });
+        let balance = Self::account_balance(who, currency);
+        let new_balance = match value {
+            Positive(p) => balance.add_balance(*p).unwrap(),
+            Negative(n) => balance.sub_balance(*n).unwrap(),
+        };
+        Self::write_balance(who, currency, new_balance.clone());
+        Self::deposit_event(RawEvent::BalanceSet(who.clone(), *currency, new_balance));
     }
 
     fn sub_balance_unsafe(
@@ -256,39 +579,50 @@ impl<T: Trait> BalanceSetter<T::AccountId, T::Balance> for Module<T> {
         currency: &currency::Currency,
         value: &SignedBalance<T::Balance>,
     ) {
-        <Account<T>>::mutate(who, currency, |balance| match value {
-            Positive(p) => {
-                *balance = balance.sub_balance(*p).unwrap();
-            }
-            Negative(n) => {
-                *balance = balance.add_balance(*n).unwrap();
-            }
-        });
+        let balance = Self::account_balance(who, currency);
+        let new_balance = match value {
+            Positive(p) => balance.sub_balance(*p).unwrap(),
+            Negative(n) => balance.add_balance(*n).unwrap(),
+        };
+        Self::write_balance(who, currency, new_balance.clone());
+        Self::deposit_event(RawEvent::BalanceSet(who.clone(), *currency, new_balance));
     }
 
     fn remove_balance_unsafe(who: &T::AccountId, currency: &currency::Currency) {
-        <Account<T>>::remove(who, currency);
+        Self::write_balance(who, currency, SignedBalance::zero());
+        Self::deposit_event(RawEvent::BalanceSet(
+            who.clone(),
+            *currency,
+            SignedBalance::zero(),
+        ));
     }
 
     fn set_balance_with_agg_unsafe(
         who: &T::AccountId,
         currency: &currency::Currency,
         value: SignedBalance<T::Balance>,
-    ) {
-        <Account<T>>::mutate(who, currency, |balance| {
-            Self::balances_aggregates_sub(&currency, &balance);
-            *balance = value;
-            Self::balances_aggregates_add(&currency, balance);
-        });
+    ) -> DispatchResult {
+        let balance = Self::account_balance(who, currency);
+        Self::update_balance(who, currency, &balance, value.clone())?;
+        Self::deposit_event(RawEvent::BalanceSet(who.clone(), *currency, value));
+        Ok(())
     }
-    fn remove_balance_with_agg_unsafe(who: &T::AccountId, currency: &currency::Currency) {
-        let balance = <Account<T>>::get(who, currency);
-        Self::balances_aggregates_sub(&currency, &balance);
-        <Account<T>>::remove(who, currency);
+    fn remove_balance_with_agg_unsafe(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+    ) -> DispatchResult {
+        let balance = Self::account_balance(who, currency);
+        Self::update_balance(who, currency, &balance, SignedBalance::zero())?;
+        Self::deposit_event(RawEvent::BalanceSet(
+            who.clone(),
+            *currency,
+            SignedBalance::zero(),
+        ));
+        Ok(())
     }
 }
 
-/// Checks if specified balance can be changed.
+/// Checks if specified balance can be changed, returns the reason if it can't.
 pub trait BalanceChecker<Balance, AccountId>
 where
     Balance: Member + Debug,
@@ -297,19 +631,19 @@ where
         _who: &AccountId,
         _currency: &currency::Currency,
         _change: &SignedBalance<Balance>,
-    ) -> bool;
+    ) -> DispatchResult;
 }
 
+/// Checkers are applied in order, the first error is returned
 #[impl_for_tuples(5)]
 impl<Balance: Member + Debug, AccountId> BalanceChecker<Balance, AccountId> for Tuple {
     fn can_change_balance(
         who: &AccountId,
         currency: &currency::Currency,
         change: &SignedBalance<Balance>,
-    ) -> bool {
-        let mut res: bool = true;
-        for_tuples!( #( res &= Tuple::can_change_balance(&who, &currency, &change); )* );
-        res
+    ) -> DispatchResult {
+        for_tuples!( #( Tuple::can_change_balance(&who, &currency, &change)?; )* );
+        Ok(())
     }
 }
 
@@ -324,13 +658,18 @@ where
         + Debug
         + Default,
 {
+    /// Imbalance of a currency created by increasing balances
+    type PositiveImbalance: Imbalance<Balance, Opposite = Self::NegativeImbalance>;
+    /// Imbalance of a currency created by decreasing balances
+    type NegativeImbalance: Imbalance<Balance, Opposite = Self::PositiveImbalance>;
+
     fn total_balance(currency: currency::Currency, who: &AccountId) -> Balance;
     fn debt(currency: currency::Currency, who: &AccountId) -> Balance;
     fn can_slash(currency: currency::Currency, who: &AccountId, value: Balance) -> bool;
     fn currency_total_issuance(currency: currency::Currency) -> Balance;
     fn currency_minimum_balance(currency: currency::Currency) -> Balance;
-    fn burn(currency: currency::Currency, amount: Balance) -> PositiveImbalance<Balance>;
-    fn issue(currency: currency::Currency, amount: Balance) -> NegativeImbalance<Balance>;
+    fn burn(currency: currency::Currency, amount: Balance) -> Self::PositiveImbalance;
+    fn issue(currency: currency::Currency, amount: Balance) -> Self::NegativeImbalance;
     fn free_balance(currency: currency::Currency, who: &AccountId) -> Balance;
     fn ensure_can_withdraw(
         currency: currency::Currency,
@@ -350,21 +689,21 @@ where
         currency: currency::Currency,
         who: &AccountId,
         value: Balance,
-    ) -> (NegativeImbalance<Balance>, Balance);
+    ) -> (Self::NegativeImbalance, Balance);
     fn deposit_into_existing(
         currency: currency::Currency,
         who: &AccountId,
         value: Balance,
-    ) -> Result<PositiveImbalance<Balance>, DispatchError>;
+    ) -> Result<Self::PositiveImbalance, DispatchError>;
     fn deposit_creating(
         currency: currency::Currency,
         who: &AccountId,
         value: Balance,
-    ) -> PositiveImbalance<Balance>;
+    ) -> Self::PositiveImbalance;
     fn resolve_creating(
         currency: currency::Currency,
         who: &AccountId,
-        value: NegativeImbalance<Balance>,
+        value: Self::NegativeImbalance,
     ) {
         let v = value.peek();
         drop(value.offset(Self::deposit_creating(currency, who, v)));
@@ -375,21 +714,30 @@ where
         value: Balance,
         reasons: WithdrawReasons,
         liveness: ExistenceRequirement,
-    ) -> Result<NegativeImbalance<Balance>, DispatchError>;
+    ) -> Result<Self::NegativeImbalance, DispatchError>;
     fn make_free_balance_be(
         currency: currency::Currency,
         who: &AccountId,
         value: Balance,
-    ) -> SignedImbalance<Balance, PositiveImbalance<Balance>>;
+    ) -> SignedImbalance<Balance, Self::PositiveImbalance>;
     fn balances_aggregates_fix();
-    fn balances_aggregates_sub(currency: &currency::Currency, balance: &SignedBalance<Balance>);
-    fn balances_aggregates_add(currency: &currency::Currency, balance: &SignedBalance<Balance>);
+    fn balances_aggregates_sub(
+        currency: &currency::Currency,
+        balance: &SignedBalance<Balance>,
+    ) -> DispatchResult;
+    fn balances_aggregates_add(
+        currency: &currency::Currency,
+        balance: &SignedBalance<Balance>,
+    ) -> DispatchResult;
     fn balances_aggregates_get(currency: &currency::Currency) -> BalancesAggregate<Balance>;
 }
 
 impl<T: Trait> EqCurrency<T::AccountId, T::Balance> for Module<T> {
+    type PositiveImbalance = PositiveImbalance<T>;
+    type NegativeImbalance = NegativeImbalance<T>;
+
     fn total_balance(currency: currency::Currency, who: &T::AccountId) -> T::Balance {
-        let balance = <Account<T>>::get(&who, &currency);
+        let balance = Self::account_balance(&who, &currency);
         match balance {
             SignedBalance::Positive(balance) => balance,
             SignedBalance::Negative(_) => T::Balance::zero(),
@@ -397,35 +745,67 @@ impl<T: Trait> EqCurrency<T::AccountId, T::Balance> for Module<T> {
     }
 
     fn debt(currency: currency::Currency, who: &T::AccountId) -> T::Balance {
-        let balance = <Account<T>>::get(&who, &currency);
+        let balance = Self::account_balance(&who, &currency);
         match balance {
             SignedBalance::Negative(balance) => balance,
             SignedBalance::Positive(_) => T::Balance::zero(),
         }
     }
 
-    fn can_slash(_currency: currency::Currency, _who: &T::AccountId, _value: T::Balance) -> bool {
-        unimplemented!("fn can_slash");
+    fn can_slash(currency: currency::Currency, who: &T::AccountId, value: T::Balance) -> bool {
+        if value.is_zero() {
+            return true;
+        }
+        Self::free_balance(currency, who) >= value
+            && T::BalanceChecker::can_change_balance(
+                &who,
+                &currency,
+                &SignedBalance::Negative(value),
+            )
+            .is_ok()
     }
 
     fn currency_total_issuance(_currency: currency::Currency) -> T::Balance {
         Self::balances_aggregates(_currency).total_issuance
     }
 
-    fn currency_minimum_balance(_currency: currency::Currency) -> T::Balance {
-        T::ExistentialDeposit::get()
+    fn currency_minimum_balance(currency: currency::Currency) -> T::Balance {
+        T::AssetGetter::get_asset(&currency)
+            .map(|a| a.existential_deposit)
+            .unwrap_or_else(T::ExistentialDeposit::get)
     }
 
-    fn burn(_currency: currency::Currency, _amount: T::Balance) -> PositiveImbalance<T::Balance> {
-        unimplemented!("fn burn");
+    /// Burns at most the total issuance of the currency
+    fn burn(currency: currency::Currency, amount: T::Balance) -> PositiveImbalance<T> {
+        if amount.is_zero() {
+            return PositiveImbalance::zero();
+        }
+        let amount = amount.min(Self::balances_aggregates(&currency).total_issuance);
+        if let Err(e) = Self::balances_aggregates_sub(&currency, &Positive(amount)) {
+            eq_error!(target: LOG_TARGET, "failed to burn {:?}: {:?}", currency, e);
+            return PositiveImbalance::zero();
+        }
+        PositiveImbalance::new(currency, amount)
     }
 
-    fn issue(_currency: currency::Currency, _amount: T::Balance) -> NegativeImbalance<T::Balance> {
-        unimplemented!("fn issue");
+    /// Issues at most the amount that keeps total issuance within `T::Balance`. Saturation
+    /// is part of the `Currency::issue` contract, which is infallible, the imbalance
+    /// carries the amount actually issued.
+    fn issue(currency: currency::Currency, amount: T::Balance) -> NegativeImbalance<T> {
+        if amount.is_zero() {
+            return NegativeImbalance::zero();
+        }
+        let amount = amount
+            .min(T::Balance::max_value() - Self::balances_aggregates(&currency).total_issuance);
+        if let Err(e) = Self::balances_aggregates_add(&currency, &Positive(amount)) {
+            eq_error!(target: LOG_TARGET, "failed to issue {:?}: {:?}", currency, e);
+            return NegativeImbalance::zero();
+        }
+        NegativeImbalance::new(currency, amount)
     }
 
     fn free_balance(currency: currency::Currency, who: &T::AccountId) -> T::Balance {
-        let balance = <Account<T>>::get(&who, &currency);
+        let balance = Self::account_balance(&who, &currency);
         match balance {
             SignedBalance::Positive(balance) => balance,
             SignedBalance::Negative(_) => T::Balance::zero(),
@@ -436,18 +816,13 @@ impl<T: Trait> EqCurrency<T::AccountId, T::Balance> for Module<T> {
         currency: currency::Currency,
         who: &T::AccountId,
         amount: T::Balance,
-        _reasons: WithdrawReasons,
-        _new_balance: T::Balance,
+        reasons: WithdrawReasons,
+        new_balance: T::Balance,
     ) -> DispatchResult {
-        ensure!(
-            T::BalanceChecker::can_change_balance(
-                &who,
-                &currency,
-                &SignedBalance::Negative(amount)
-            ),
-            Error::<T>::NotAllowedToChangeBalance
-        );
-        Ok(())
+        Self::ensure_currency_active(&currency)?;
+        Self::ensure_not_frozen(who, &currency)?;
+        T::BalanceChecker::can_change_balance(&who, &currency, &SignedBalance::Negative(amount))?;
+        Self::ensure_not_locked(currency, who, reasons, new_balance)
     }
 
     fn currency_transfer(
@@ -455,153 +830,183 @@ impl<T: Trait> EqCurrency<T::AccountId, T::Balance> for Module<T> {
         transactor: &T::AccountId,
         dest: &T::AccountId,
         value: T::Balance,
-        _existence_requirement: ExistenceRequirement,
+        existence_requirement: ExistenceRequirement,
     ) -> DispatchResult {
         if value.is_zero() || transactor == dest {
             return Ok(());
         }
 
-        <Account<T>>::mutate(transactor, &currency, |from_account| -> DispatchResult {
-            <Account<T>>::mutate(dest, &currency, |to_account| -> DispatchResult {
-                ensure!(
-                    T::BalanceChecker::can_change_balance(
-                        &transactor,
-                        &currency,
-                        &SignedBalance::Negative(value)
-                    ),
-                    Error::<T>::NotAllowedToChangeBalance
-                );
-                ensure!(
-                    T::BalanceChecker::can_change_balance(
-                        &dest,
-                        &currency,
-                        &SignedBalance::Positive(value)
-                    ),
-                    Error::<T>::NotAllowedToChangeBalance
-                );
-
-                Self::balances_aggregates_sub(&currency, &from_account);
-                Self::balances_aggregates_sub(&currency, &to_account);
+        Self::ensure_currency_active(&currency)?;
+        Self::ensure_not_frozen(transactor, &currency)?;
+        T::BalanceChecker::can_change_balance(
+            &transactor,
+            &currency,
+            &SignedBalance::Negative(value),
+        )?;
+        T::BalanceChecker::can_change_balance(&dest, &currency, &SignedBalance::Positive(value))?;
+
+        let from_account = Self::account_balance(transactor, &currency);
+        let to_account = Self::account_balance(dest, &currency);
+
+        let new_from_account = from_account
+            .sub_balance(value)
+            .ok_or(Error::<T>::Overflow)?;
+        let new_to_account = to_account.add_balance(value).ok_or(Error::<T>::Overflow)?;
+
+        Self::ensure_not_locked(
+            currency,
+            transactor,
+            WithdrawReason::Transfer.into(),
+            Self::free_balance_of(&new_from_account),
+        )?;
+        let dust = Self::dust_of(currency, &new_from_account, existence_requirement)?;
+        ensure!(
+            !to_account.is_zero()
+                || Self::free_balance_of(&new_to_account)
+                    >= Self::currency_minimum_balance(currency),
+            Error::<T>::ExistentialDeposit
+        );
 
-                *from_account = from_account
-                    .sub_balance(value)
-                    .ok_or(Error::<T>::Overflow)?;
-                *to_account = to_account.add_balance(value).ok_or(Error::<T>::Overflow)?;
+        Self::update_balances(
+            &currency,
+            vec![
+                (transactor, from_account, new_from_account),
+                (dest, to_account, new_to_account),
+            ],
+        )?;
+        Self::reap_dust(currency, transactor, dust);
+
+        Self::deposit_event(RawEvent::Transfer(
+            transactor.clone(),
+            dest.clone(),
+            currency.clone(),
+            value,
+        ));
+        Ok(())
+    }
 
-                Self::balances_aggregates_add(&currency, &from_account);
-                Self::balances_aggregates_add(&currency, &to_account);
+    /// Slashes as much as possible from the positive part of the balance. Accounts with
+    /// negative balance have nothing to slash, so debt is never increased. Nothing is
+    /// slashed when `BalanceChecker` rejects the change, same as in `can_slash`.
+    fn slash(
+        currency: currency::Currency,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> (NegativeImbalance<T>, T::Balance) {
+        if value.is_zero() {
+            return (NegativeImbalance::zero(), T::Balance::zero());
+        }
 
-                Ok(())
-            })?;
+        let account = Self::account_balance(who, &currency);
+        let slashed = value.min(Self::free_balance_of(&account));
+        if slashed.is_zero()
+            || T::BalanceChecker::can_change_balance(
+                &who,
+                &currency,
+                &SignedBalance::Negative(slashed),
+            )
+            .is_err()
+        {
+            return (NegativeImbalance::zero(), value);
+        }
 
-            Self::deposit_event(RawEvent::Transfer(
-                transactor.clone(),
-                dest.clone(),
-                currency.clone(),
-                value,
-            ));
-            Ok(())
-        })
-    }
+        let new_account = SignedBalance::Positive(Self::free_balance_of(&account) - slashed);
+        let dust = Self::dust_of(currency, &new_account, ExistenceRequirement::AllowDeath)
+            .unwrap_or_else(|_| T::Balance::zero());
+        if let Err(e) = Self::update_balance_unbalanced(
+            who,
+            &currency,
+            &account,
+            new_account,
+            Negative(slashed),
+        ) {
+            eq_error!(target: LOG_TARGET, "failed to slash {:?}: {:?}", who, e);
+            return (NegativeImbalance::zero(), value);
+        }
+        Self::reap_dust(currency, who, dust);
 
-    fn slash(
-        _currency: currency::Currency,
-        _who: &T::AccountId,
-        _value: T::Balance,
-    ) -> (NegativeImbalance<T::Balance>, T::Balance) {
-        unimplemented!("fn slash");
+        Self::deposit_event(RawEvent::Slashed(who.clone(), currency, slashed));
+        (NegativeImbalance::new(currency, slashed), value - slashed)
     }
 
     fn deposit_into_existing(
         currency: currency::Currency,
         who: &T::AccountId,
         value: T::Balance,
-    ) -> Result<PositiveImbalance<T::Balance>, DispatchError> {
+    ) -> Result<PositiveImbalance<T>, DispatchError> {
         if value.is_zero() {
             return Ok(PositiveImbalance::zero());
         }
 
-        <Account<T>>::mutate(
-            &who,
-            &currency,
-            |to_account| -> Result<PositiveImbalance<T::Balance>, DispatchError> {
-                ensure!(
-                    T::BalanceChecker::can_change_balance(
-                        &who,
-                        &currency,
-                        &SignedBalance::Positive(value)
-                    ),
-                    Error::<T>::NotAllowedToChangeBalance
-                );
-                Self::balances_aggregates_sub(&currency, &to_account);
-                *to_account = to_account.add_balance(value).ok_or(Error::<T>::Overflow)?;
-                Self::balances_aggregates_add(&currency, &to_account);
-                Ok(PositiveImbalance::new(value))
-            },
-        )
+        Self::ensure_currency_active(&currency)?;
+        T::BalanceChecker::can_change_balance(&who, &currency, &SignedBalance::Positive(value))?;
+        let account = Self::account_balance(who, &currency);
+        let new_account = account.add_balance(value).ok_or(Error::<T>::Overflow)?;
+        Self::update_balance_unbalanced(who, &currency, &account, new_account, Positive(value))?;
+        Self::deposit_event(RawEvent::Deposit(who.clone(), currency, value));
+        Ok(PositiveImbalance::new(currency, value))
     }
 
     fn deposit_creating(
         currency: currency::Currency,
         who: &T::AccountId,
         value: T::Balance,
-    ) -> PositiveImbalance<T::Balance> {
+    ) -> PositiveImbalance<T> {
         if value.is_zero() {
             return PositiveImbalance::zero();
         }
 
-        <Account<T>>::mutate(
-            &who,
-            &currency,
-            |to_account| -> Result<PositiveImbalance<T::Balance>, PositiveImbalance<T::Balance>> {
-                if T::BalanceChecker::can_change_balance(
-                    &who,
-                    &currency,
-                    &SignedBalance::Positive(value),
-                ) {
-                    Self::balances_aggregates_sub(&currency, &to_account);
-                    *to_account = to_account
-                        .add_balance(value)
-                        .ok_or(PositiveImbalance::zero())?;
-                    Self::balances_aggregates_add(&currency, &to_account);
-                    Ok(PositiveImbalance::new(value))
-                } else {
-                    Ok(PositiveImbalance::zero())
-                }
-            },
-        )
-        .unwrap_or_else(|x| x)
+        let account = Self::account_balance(who, &currency);
+        if account.is_zero() && value < Self::currency_minimum_balance(currency) {
+            return PositiveImbalance::zero();
+        }
+        if Self::ensure_currency_active(&currency).is_err()
+            || T::BalanceChecker::can_change_balance(
+                &who,
+                &currency,
+                &SignedBalance::Positive(value),
+            )
+            .is_err()
+        {
+            return PositiveImbalance::zero();
+        }
+
+        let new_account = match account.add_balance(value) {
+            Some(new_account) => new_account,
+            None => return PositiveImbalance::zero(),
+        };
+        if let Err(e) =
+            Self::update_balance_unbalanced(who, &currency, &account, new_account, Positive(value))
+        {
+            eq_error!(target: LOG_TARGET, "failed to deposit to {:?}: {:?}", who, e);
+            return PositiveImbalance::zero();
+        }
+        Self::deposit_event(RawEvent::Deposit(who.clone(), currency, value));
+        PositiveImbalance::new(currency, value)
     }
 
     fn withdraw(
         currency: currency::Currency,
-        _who: &T::AccountId,
+        who: &T::AccountId,
         value: T::Balance,
-        _reasons: WithdrawReasons,
-        _liveness: ExistenceRequirement,
-    ) -> result::Result<NegativeImbalance<T::Balance>, DispatchError> {
+        reasons: WithdrawReasons,
+        liveness: ExistenceRequirement,
+    ) -> result::Result<NegativeImbalance<T>, DispatchError> {
         if value.is_zero() {
             return Ok(NegativeImbalance::zero());
         }
 
-        <Account<T>>::mutate(
-            _who,
-            &currency,
-            |to_account| -> Result<NegativeImbalance<T::Balance>, DispatchError> {
-                ensure!(
-                    T::BalanceChecker::can_change_balance(
-                        &_who,
-                        &currency,
-                        &SignedBalance::Negative(value)
-                    ),
-                    Error::<T>::NotAllowedToChangeBalance
-                );
-                Self::balances_aggregates_sub(&currency, &to_account);
-                *to_account = to_account.sub_balance(value).ok_or(Error::<T>::Overflow)?;
-                Self::balances_aggregates_add(&currency, &to_account);
-                Ok(NegativeImbalance::new(value))
-            },
-        )
+        Self::ensure_currency_active(&currency)?;
+        Self::ensure_not_frozen(who, &currency)?;
+        T::BalanceChecker::can_change_balance(&who, &currency, &SignedBalance::Negative(value))?;
+        let account = Self::account_balance(who, &currency);
+        let new_account = account.sub_balance(value).ok_or(Error::<T>::Overflow)?;
+        Self::ensure_not_locked(currency, who, reasons, Self::free_balance_of(&new_account))?;
+        let dust = Self::dust_of(currency, &new_account, liveness)?;
+        Self::update_balance_unbalanced(who, &currency, &account, new_account, Negative(value))?;
+        Self::reap_dust(currency, who, dust);
+        Self::deposit_event(RawEvent::Withdraw(who.clone(), currency, value));
+        Ok(NegativeImbalance::new(currency, value))
     }
 
     /// Force the new free balance of a target account `who` to some new value `balance`.
@@ -609,88 +1014,539 @@ impl<T: Trait> EqCurrency<T::AccountId, T::Balance> for Module<T> {
         currency: currency::Currency,
         who: &T::AccountId,
         value: T::Balance,
-    ) -> SignedImbalance<T::Balance, PositiveImbalance<T::Balance>> {
-        <Account<T>>::mutate(
+    ) -> SignedImbalance<T::Balance, PositiveImbalance<T>> {
+        let account = Self::account_balance(who, &currency);
+        let change = match account {
+            SignedBalance::Positive(balance) => {
+                if value > balance {
+                    SignedBalance::Positive(value - balance)
+                } else {
+                    SignedBalance::Negative(balance - value)
+                }
+            }
+            SignedBalance::Negative(balance) => match value.checked_add(&balance) {
+                Some(change) => SignedBalance::Positive(change),
+                None => {
+                    eq_error!(
+                        target: LOG_TARGET,
+                        "failed to set balance of {:?}: {:?}",
+                        who,
+                        Error::<T>::Overflow
+                    );
+                    return SignedImbalance::Positive(PositiveImbalance::zero());
+                }
+            },
+        };
+
+        if T::BalanceChecker::can_change_balance(&who, &currency, &change).is_err() {
+            return SignedImbalance::Positive(PositiveImbalance::zero());
+        }
+
+        if let Err(e) = Self::update_balance_unbalanced(
             who,
             &currency,
-            |account| -> Result<SignedImbalance<T::Balance, PositiveImbalance<T::Balance>>, ()> {
-                let imbalance = match account {
-                    SignedBalance::Positive(balance) => {
-                        let a_balance = balance.clone();
-                        if value > a_balance {
-                            SignedImbalance::Positive(PositiveImbalance::new(value - a_balance))
-                        } else {
-                            SignedImbalance::Negative(NegativeImbalance::new(a_balance - value))
-                        }
-                    }
-                    SignedBalance::Negative(balance) => {
-                        let a_balance = balance.clone();
-                        SignedImbalance::Positive(PositiveImbalance::new(value + a_balance))
-                    }
-                };
+            &account,
+            Positive(value),
+            change.clone(),
+        ) {
+            eq_error!(target: LOG_TARGET, "failed to set balance of {:?}: {:?}", who, e);
+            return SignedImbalance::Positive(PositiveImbalance::zero());
+        }
+        Self::deposit_event(RawEvent::BalanceSet(
+            who.clone(),
+            currency,
+            SignedBalance::Positive(value),
+        ));
+        // the imbalance is only created after the balance is written, it settles the
+        // aggregates when dropped
+        match change {
+            SignedBalance::Positive(amount) => {
+                SignedImbalance::Positive(PositiveImbalance::new(currency, amount))
+            }
+            SignedBalance::Negative(amount) => {
+                SignedImbalance::Negative(NegativeImbalance::new(currency, amount))
+            }
+        }
+    }
 
-                ensure!(
-                    T::BalanceChecker::can_change_balance(
-                        &who,
-                        &currency,
-                        &SignedBalance::from(&imbalance)
-                    ),
-                    ()
-                );
+    /// Recomputes aggregates of all currencies, reads the whole state
+    fn balances_aggregates_fix() {
+        let totals = match Self::compute_aggregates() {
+            Ok(totals) => totals,
+            Err(e) => {
+                eq_error!(target: LOG_TARGET, "failed to compute aggregates: {:?}", e);
+                return;
+            }
+        };
+        for currency in T::AssetGetter::currencies() {
+            <BalancesAggregates<T>>::insert(currency, BalancesAggregate::default());
+        }
+        for (currency, aggregate) in totals.iter() {
+            <BalancesAggregates<T>>::insert(currency, aggregate);
+        }
+    }
+    fn balances_aggregates_sub(
+        currency: &currency::Currency,
+        balance: &SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        <BalancesAggregates<T>>::try_mutate(currency, |currency_aggregate| {
+            *currency_aggregate = currency_aggregate
+                .checked_sub(balance)
+                .ok_or(Error::<T>::AggregatesUnderflow)?;
+            Ok(())
+        })
+    }
+    fn balances_aggregates_add(
+        currency: &currency::Currency,
+        balance: &SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        <BalancesAggregates<T>>::try_mutate(currency, |currency_aggregate| {
+            *currency_aggregate = currency_aggregate
+                .checked_add(balance)
+                .ok_or(Error::<T>::Overflow)?;
+            Ok(())
+        })
+    }
+    fn balances_aggregates_get(currency: &currency::Currency) -> BalancesAggregate<T::Balance> {
+        <BalancesAggregates<T>>::get(currency)
+    }
+}
 
-                *account = SignedBalance::Positive(value);
+impl<T: Trait> Module<T> {
+    /// All non-zero balances of the account
+    pub fn balances_of(who: &T::AccountId) -> Vec<(currency::Currency, SignedBalance<T::Balance>)> {
+        <Accounts<T>>::get(who).balances().to_vec()
+    }
 
-                Ok(imbalance)
-            },
-        )
-        .unwrap_or(SignedImbalance::Positive(PositiveImbalance::zero()))
+    /// Whether the account can't move funds of the currency
+    pub fn is_frozen(who: &T::AccountId, currency: &currency::Currency) -> bool {
+        <Freezes<T>>::get(who, None::<currency::Currency>)
+            || <Freezes<T>>::get(who, Some(*currency))
     }
 
-    fn balances_aggregates_fix() {
-        for currency in currency::Currency::iterator_with_usd() {
-            <BalancesAggregates<T>>::mutate(currency, |currency_aggregate| {
-                currency_aggregate.total_issuance = T::Balance::zero();
-                currency_aggregate.total_debt = T::Balance::zero();
-            });
-        }
-        for (_a, c, b) in <Account<T>>::iter() {
-            <BalancesAggregates<T>>::mutate(c, |currency_aggregate| match b {
-                Positive(p) => {
-                    currency_aggregate.total_issuance += p;
-                }
-                Negative(n) => {
-                    currency_aggregate.total_debt += n;
-                }
-            });
+    fn ensure_not_frozen(who: &T::AccountId, currency: &currency::Currency) -> DispatchResult {
+        ensure!(!Self::is_frozen(who, currency), Error::<T>::AccountFrozen);
+        Ok(())
+    }
+
+    /// Currencies missing from the registry are treated as active
+    fn ensure_currency_active(currency: &currency::Currency) -> DispatchResult {
+        let status = T::AssetGetter::get_asset(currency).map(|a| a.status);
+        ensure!(
+            status.map_or(true, |s| s == AssetStatus::Active),
+            Error::<T>::CurrencyNotActive
+        );
+        Ok(())
+    }
+
+    fn account_balance(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+    ) -> SignedBalance<T::Balance> {
+        <Accounts<T>>::get(who).balance(currency)
+    }
+
+    fn free_balance_of(balance: &SignedBalance<T::Balance>) -> T::Balance {
+        match balance {
+            SignedBalance::Positive(balance) => *balance,
+            SignedBalance::Negative(_) => T::Balance::zero(),
         }
     }
-    fn balances_aggregates_sub(currency: &currency::Currency, balance: &SignedBalance<T::Balance>) {
-        <BalancesAggregates<T>>::mutate(currency, |currency_aggregate| match balance {
-            Positive(p) => {
-                currency_aggregate.total_issuance -= *p;
+
+    /// Replaces `old_balance` of `who` with `new_balance` keeping aggregates consistent
+    fn update_balance(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        old_balance: &SignedBalance<T::Balance>,
+        new_balance: SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        Self::update_balances(currency, vec![(who, old_balance.clone(), new_balance)])
+    }
+
+    /// Replaces `old_balance` of `who` with `new_balance` leaving `imbalance` out of the
+    /// aggregates, it is settled when the returned imbalance is dropped
+    fn update_balance_unbalanced(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        old_balance: &SignedBalance<T::Balance>,
+        new_balance: SignedBalance<T::Balance>,
+        imbalance: SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        let aggregate =
+            Self::aggregate_after(currency, &[(who, old_balance.clone(), new_balance.clone())])?;
+        let aggregate = match imbalance {
+            Positive(amount) => aggregate
+                .checked_sub(&Positive(amount))
+                .ok_or(Error::<T>::AggregatesUnderflow)?,
+            Negative(amount) => aggregate
+                .checked_add(&Positive(amount))
+                .ok_or(Error::<T>::Overflow)?,
+        };
+        <BalancesAggregates<T>>::insert(currency, aggregate);
+        Self::write_balance(who, currency, new_balance);
+        Ok(())
+    }
+
+    /// Replaces old balances of the accounts with new ones. Aggregates are checked
+    /// before anything is written, so either all balances are updated or none.
+    fn update_balances(
+        currency: &currency::Currency,
+        changes: Vec<(
+            &T::AccountId,
+            SignedBalance<T::Balance>,
+            SignedBalance<T::Balance>,
+        )>,
+    ) -> DispatchResult {
+        let aggregate = Self::aggregate_after(currency, &changes)?;
+        <BalancesAggregates<T>>::insert(currency, aggregate);
+        for (who, _, new_balance) in changes.into_iter() {
+            Self::write_balance(who, currency, new_balance);
+        }
+        Ok(())
+    }
+
+    /// Aggregate of the currency after the balance changes, fails instead of wrapping
+    fn aggregate_after(
+        currency: &currency::Currency,
+        changes: &[(
+            &T::AccountId,
+            SignedBalance<T::Balance>,
+            SignedBalance<T::Balance>,
+        )],
+    ) -> Result<BalancesAggregate<T::Balance>, DispatchError> {
+        let mut aggregate = <BalancesAggregates<T>>::get(currency);
+        for (_, old_balance, new_balance) in changes.iter() {
+            aggregate = aggregate
+                .checked_sub(old_balance)
+                .ok_or(Error::<T>::AggregatesUnderflow)?
+                .checked_add(new_balance)
+                .ok_or(Error::<T>::Overflow)?;
+        }
+        Ok(aggregate)
+    }
+
+    /// Stores the balance, removing zero balances from storage. The account holds a
+    /// `frame_system` reference while it has at least one balance, reserve or lock and
+    /// is killed when the last one is removed and nobody else references it.
+    fn write_balance(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        balance: SignedBalance<T::Balance>,
+    ) {
+        let mut account = <Accounts<T>>::get(who);
+        let existed = !account.is_empty();
+        let old_balance = account.balance(currency);
+        Self::track_repair(
+            &<Accounts<T>>::hashed_key_for(who),
+            currency,
+            &old_balance,
+            &balance,
+        );
+        if old_balance != balance {
+            Self::record_checkpoint(who, currency, &old_balance);
+        }
+        match (&old_balance, &balance) {
+            (Positive(_), Negative(debt)) if !debt.is_zero() => {
+                Self::deposit_event(RawEvent::DebtCreated(who.clone(), *currency, *debt));
             }
-            Negative(n) => {
-                currency_aggregate.total_debt -= *n;
+            (Negative(debt), Positive(_)) if !debt.is_zero() => {
+                Self::deposit_event(RawEvent::DebtRepaid(who.clone(), *currency, *debt));
             }
-        });
+            _ => {}
+        }
+
+        account.set_balance(currency, balance);
+        if account.is_empty() {
+            if existed {
+                <Accounts<T>>::remove(who);
+                Self::on_entry_removed(who);
+            }
+        } else {
+            if !existed {
+                Self::on_entry_created(who);
+            }
+            <Accounts<T>>::insert(who, account);
+        }
+    }
+
+    /// Must be called before the first balance, reserve or lock entry of the account is stored
+    fn on_entry_created(who: &T::AccountId) {
+        if Self::is_account_empty(who) {
+            system::Module::<T>::inc_ref(who);
+        }
     }
-    fn balances_aggregates_add(currency: &currency::Currency, balance: &SignedBalance<T::Balance>) {
-        <BalancesAggregates<T>>::mutate(currency, |currency_aggregate| match balance {
-            Positive(p) => {
-                currency_aggregate.total_issuance += *p;
+
+    /// Must be called after balance, reserve or lock entry of the account is removed
+    fn on_entry_removed(who: &T::AccountId) {
+        if Self::is_account_empty(who) {
+            system::Module::<T>::dec_ref(who);
+            if system::Module::<T>::refs(who).is_zero() {
+                system::CallKillAccount::<T>::happened(who);
             }
-            Negative(n) => {
-                currency_aggregate.total_debt += *n;
+        }
+    }
+
+    fn is_account_empty(who: &T::AccountId) -> bool {
+        !<Accounts<T>>::contains_key(who)
+            && <Reserves<T>>::iter_prefix(who).next().is_none()
+            && <Locks<T>>::iter_prefix(who).next().is_none()
+    }
+
+    /// Returns the part of `new_balance` below existential deposit that should be
+    /// collected as dust. Fails if the account is required to stay alive.
+    fn dust_of(
+        currency: currency::Currency,
+        new_balance: &SignedBalance<T::Balance>,
+        liveness: ExistenceRequirement,
+    ) -> Result<T::Balance, DispatchError> {
+        match new_balance {
+            SignedBalance::Positive(balance)
+                if *balance < Self::currency_minimum_balance(currency) =>
+            {
+                ensure!(
+                    liveness == ExistenceRequirement::AllowDeath,
+                    Error::<T>::KeepAlive
+                );
+                Ok(*balance)
             }
-        });
+            _ => Ok(T::Balance::zero()),
+        }
     }
-    fn balances_aggregates_get(currency: &currency::Currency) -> BalancesAggregate<T::Balance> {
-        <BalancesAggregates<T>>::get(currency)
+
+    /// Applies all transfers or none of them. Every leg is checked by `BalanceChecker`
+    /// with the previous legs applied, storage is written only after all legs pass.
+    fn currency_transfer_many(
+        from: &T::AccountId,
+        transfers: &[(currency::Currency, T::AccountId, T::Balance)],
+    ) -> DispatchResult {
+        let mut changes: Vec<BalanceChange<T>> = Vec::new();
+
+        for (currency, to, value) in transfers.iter() {
+            if value.is_zero() || to == from {
+                continue;
+            }
+
+            Self::ensure_currency_active(currency)?;
+            Self::ensure_not_frozen(from, currency)?;
+
+            let index = Self::staged_balance(&mut changes, from, *currency);
+            changes[index].3 = changes[index]
+                .3
+                .sub_balance(*value)
+                .ok_or(Error::<T>::Overflow)?;
+            let index = Self::staged_balance(&mut changes, to, *currency);
+            changes[index].3 = changes[index]
+                .3
+                .add_balance(*value)
+                .ok_or(Error::<T>::Overflow)?;
+        }
+
+        Self::check_transfers(from, transfers)?;
+
+        let mut dust: Vec<(currency::Currency, T::Balance)> = Vec::new();
+        for (who, currency, old_balance, new_balance) in changes.iter() {
+            if who == from {
+                Self::ensure_not_locked(
+                    *currency,
+                    from,
+                    WithdrawReason::Transfer.into(),
+                    Self::free_balance_of(new_balance),
+                )?;
+                dust.push((
+                    *currency,
+                    Self::dust_of(*currency, new_balance, ExistenceRequirement::AllowDeath)?,
+                ));
+            } else {
+                ensure!(
+                    !old_balance.is_zero()
+                        || Self::free_balance_of(new_balance)
+                            >= Self::currency_minimum_balance(*currency),
+                    Error::<T>::ExistentialDeposit
+                );
+            }
+        }
+
+        let mut aggregates: Vec<(currency::Currency, BalancesAggregate<T::Balance>)> = Vec::new();
+        for (_, currency, _, _) in changes.iter() {
+            if aggregates.iter().all(|(c, _)| c != currency) {
+                let currency_changes = changes
+                    .iter()
+                    .filter(|(_, c, _, _)| c == currency)
+                    .map(|(who, _, old_balance, new_balance)| {
+                        (who, old_balance.clone(), new_balance.clone())
+                    })
+                    .collect::<Vec<_>>();
+                aggregates.push((
+                    *currency,
+                    Self::aggregate_after(currency, &currency_changes)?,
+                ));
+            }
+        }
+        for (currency, aggregate) in aggregates.iter() {
+            <BalancesAggregates<T>>::insert(currency, aggregate);
+        }
+        for (who, currency, _, new_balance) in changes.into_iter() {
+            Self::write_balance(&who, &currency, new_balance);
+        }
+        for (currency, dust) in dust.into_iter() {
+            Self::reap_dust(currency, from, dust);
+        }
+
+        for (currency, to, value) in transfers.iter() {
+            if !value.is_zero() && to != from {
+                Self::deposit_event(RawEvent::Transfer(
+                    from.clone(),
+                    to.clone(),
+                    *currency,
+                    *value,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs `BalanceChecker` for every leg with balances of the previous legs written to
+    /// storage, so checkers see what the batch has changed. All writes are rolled back.
+    fn check_transfers(
+        from: &T::AccountId,
+        transfers: &[(currency::Currency, T::AccountId, T::Balance)],
+    ) -> DispatchResult {
+        with_transaction(|| {
+            let result = transfers
+                .iter()
+                .filter(|(_, to, value)| !value.is_zero() && to != from)
+                .try_for_each(|(currency, to, value)| -> DispatchResult {
+                    T::BalanceChecker::can_change_balance(from, currency, &Negative(*value))?;
+                    T::BalanceChecker::can_change_balance(to, currency, &Positive(*value))?;
+                    Self::stage_in_storage(from, currency, &Negative(*value))?;
+                    Self::stage_in_storage(to, currency, &Positive(*value))
+                });
+            TransactionOutcome::Rollback(result)
+        })
+    }
+
+    /// Writes the changed balance bypassing aggregates, events and references, only for
+    /// storage changes that are rolled back
+    fn stage_in_storage(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        change: &SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        <Accounts<T>>::try_mutate(who, |account| {
+            let balance = match change {
+                Positive(value) => account.balance(currency).add_balance(*value),
+                Negative(value) => account.balance(currency).sub_balance(*value),
+            };
+            account.set_balance(currency, balance.ok_or(Error::<T>::Overflow)?);
+            Ok(())
+        })
+    }
+
+    /// Index of the account balance in `changes`, adding the stored balance when missing
+    fn staged_balance(
+        changes: &mut Vec<BalanceChange<T>>,
+        who: &T::AccountId,
+        currency: currency::Currency,
+    ) -> usize {
+        match changes
+            .iter()
+            .position(|(w, c, _, _)| w == who && *c == currency)
+        {
+            Some(index) => index,
+            None => {
+                let balance = Self::account_balance(who, &currency);
+                changes.push((who.clone(), currency, balance.clone(), balance));
+                changes.len() - 1
+            }
+        }
+    }
+
+    /// Moves dust left on the account to the treasury
+    fn reap_dust(currency: currency::Currency, who: &T::AccountId, dust: T::Balance) {
+        let treasury = T::TreasuryAccountGetter::get_account_id();
+        if dust.is_zero() || *who == treasury {
+            return;
+        }
+        let treasury_account = Self::account_balance(&treasury, &currency);
+        if let Some(new_treasury_account) = treasury_account.add_balance(dust) {
+            let account = Self::account_balance(who, &currency);
+            if let Err(e) = Self::update_balances(
+                &currency,
+                vec![
+                    (who, account, SignedBalance::zero()),
+                    (&treasury, treasury_account, new_treasury_account),
+                ],
+            ) {
+                eq_error!(target: LOG_TARGET, "failed to reap dust of {:?}: {:?}", who, e);
+                return;
+            }
+            Self::deposit_event(RawEvent::AccountReaped(who.clone(), currency, dust));
+        }
+    }
+
+    /// Runs the pending storage migration. Not a module hook: the runtime calls it from
+    /// its `OnRuntimeUpgrade` before migrations of modules that move balances.
+    pub fn migrate() -> Weight {
+        if StorageVersion::get() == Releases::V1DoubleMap {
+            Self::migrate_to_single_entry()
+        } else {
+            T::DbWeight::get().reads(1)
+        }
+    }
+
+    /// Moves balances from the `Account` double map into a single `Accounts` entry
+    /// per account and takes a system reference for every migrated account. Runs in a
+    /// single block, the weight covers every drained entry.
+    pub(crate) fn migrate_to_single_entry() -> Weight {
+        let mut count: u32 = 0;
+        for (key, balance) in
+            StorageIterator::<SignedBalance<T::Balance>>::new(b"EqBalances", b"Account").drain()
+        {
+            count += 1;
+            match Self::decode_account_key(&key) {
+                Some((who, currency)) => Self::migrate_balance(&who, &currency, balance),
+                None => eq_warn!(target: LOG_TARGET, "skipped undecodable account key {:?}", key),
+            }
+        }
+        StorageVersion::put(Releases::V2SingleEntry);
+
+        T::WeightInfo::migrate_accounts(count)
+    }
+
+    /// Adds the balance to the account entry. Accounts of the double map never held a
+    /// `frame_system` reference, it is taken when their entry is created.
+    fn migrate_balance(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        balance: SignedBalance<T::Balance>,
+    ) {
+        let existed = <Accounts<T>>::contains_key(who);
+        let mut account = <Accounts<T>>::get(who);
+        account.set_balance(currency, balance);
+        if account.is_empty() {
+            return;
+        }
+        if !existed {
+            system::Module::<T>::inc_ref(who);
+        }
+        <Accounts<T>>::insert(who, account);
+    }
+
+    /// Decodes `blake2_128_concat(who) ++ blake2_128_concat(currency)` key of the
+    /// `Account` double map
+    fn decode_account_key(key: &[u8]) -> Option<(T::AccountId, currency::Currency)> {
+        let mut input = key.get(16..)?;
+        let who = T::AccountId::decode(&mut input).ok()?;
+        let mut input = input.get(16..)?;
+        let currency = currency::Currency::decode(&mut input).ok()?;
+        Some((who, currency))
     }
 }
 
 impl<T: Trait> OnKilledAccount<T::AccountId> for Module<T> {
     fn on_killed_account(who: &T::AccountId) {
-        Account::<T>::remove_prefix(who);
+        Accounts::<T>::remove(who);
+        Reserves::<T>::remove_prefix(who);
+        Locks::<T>::remove_prefix(who);
     }
 }
@@ -0,0 +1,219 @@
+use super::*;
+use frame_support::traits::LockIdentifier;
+use sp_runtime::RuntimeDebug;
+use sp_std::ops::BitOr;
+
+/// Simplified reasons for withdrawing balance.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Reasons {
+    /// Paying system transaction fees.
+    Fee = 0,
+    /// Any reason other than paying system transaction fees.
+    Misc = 1,
+    /// Any reason at all.
+    All = 2,
+}
+
+impl From<WithdrawReasons> for Reasons {
+    fn from(r: WithdrawReasons) -> Reasons {
+        if r == WithdrawReasons::from(WithdrawReason::TransactionPayment) {
+            Reasons::Fee
+        } else if r.contains(WithdrawReason::TransactionPayment) {
+            Reasons::All
+        } else {
+            Reasons::Misc
+        }
+    }
+}
+
+impl BitOr for Reasons {
+    type Output = Reasons;
+    fn bitor(self, other: Reasons) -> Reasons {
+        if self == other {
+            return self;
+        }
+        Reasons::All
+    }
+}
+
+impl Reasons {
+    fn intersects(&self, other: &Reasons) -> bool {
+        match (self, other) {
+            (Reasons::All, _) | (_, Reasons::All) => true,
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// A single lock on a balance in some currency
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct BalanceLock<Balance> {
+    /// An identifier for this lock. Only one lock may be in existence for each identifier.
+    pub id: LockIdentifier,
+    /// The amount which should stay on the account while the lock exists.
+    pub amount: Balance,
+    /// Withdrawals this lock applies to.
+    pub reasons: Reasons,
+}
+
+/// Keeps part of the balance in different currencies on the account
+pub trait EqLockableCurrency<AccountId, Balance>: EqCurrency<AccountId, Balance>
+where
+    Balance: Member
+        + AtLeast32BitUnsigned
+        + FullCodec
+        + Copy
+        + MaybeSerializeDeserialize
+        + Debug
+        + Default,
+{
+    fn set_lock(
+        currency: currency::Currency,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Balance,
+        reasons: WithdrawReasons,
+    );
+    fn extend_lock(
+        currency: currency::Currency,
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Balance,
+        reasons: WithdrawReasons,
+    );
+    fn remove_lock(currency: currency::Currency, id: LockIdentifier, who: &AccountId);
+}
+
+impl<T: Trait> EqLockableCurrency<T::AccountId, T::Balance> for Module<T> {
+    /// Creates or replaces the lock with `id`. Zero amount or empty reasons is a no-op.
+    fn set_lock(
+        currency: currency::Currency,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() || reasons.is_none() {
+            return;
+        }
+        let mut new_lock = Some(BalanceLock {
+            id,
+            amount,
+            reasons: reasons.into(),
+        });
+        let mut locks = <Locks<T>>::get(who, &currency)
+            .into_iter()
+            .filter_map(|lock| {
+                if lock.id == id {
+                    new_lock.take()
+                } else {
+                    Some(lock)
+                }
+            })
+            .collect::<Vec<_>>();
+        if let Some(lock) = new_lock {
+            locks.push(lock)
+        }
+        Self::write_locks(who, &currency, locks);
+    }
+
+    /// Extends the lock with `id` to the maximum of the amounts and the union of reasons,
+    /// creates the lock if it does not exist.
+    fn extend_lock(
+        currency: currency::Currency,
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() || reasons.is_none() {
+            return;
+        }
+        let mut new_lock = Some(BalanceLock {
+            id,
+            amount,
+            reasons: reasons.into(),
+        });
+        let mut locks = <Locks<T>>::get(who, &currency)
+            .into_iter()
+            .filter_map(|lock| {
+                if lock.id == id {
+                    new_lock.take().map(|nl| BalanceLock {
+                        id: lock.id,
+                        amount: lock.amount.max(nl.amount),
+                        reasons: lock.reasons | nl.reasons,
+                    })
+                } else {
+                    Some(lock)
+                }
+            })
+            .collect::<Vec<_>>();
+        if let Some(lock) = new_lock {
+            locks.push(lock)
+        }
+        Self::write_locks(who, &currency, locks);
+    }
+
+    fn remove_lock(currency: currency::Currency, id: LockIdentifier, who: &T::AccountId) {
+        let mut locks = <Locks<T>>::get(who, &currency);
+        locks.retain(|lock| lock.id != id);
+        Self::write_locks(who, &currency, locks);
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Part of the balance that has to stay on the account for withdrawals with `reasons`
+    pub fn frozen_balance(
+        currency: currency::Currency,
+        who: &T::AccountId,
+        reasons: WithdrawReasons,
+    ) -> T::Balance {
+        let reasons = Reasons::from(reasons);
+        <Locks<T>>::get(who, &currency)
+            .iter()
+            .filter(|lock| lock.reasons.intersects(&reasons))
+            .fold(T::Balance::zero(), |frozen, lock| frozen.max(lock.amount))
+    }
+
+    /// Fails if `new_balance` left on the account after withdrawal breaks any of its locks
+    pub(crate) fn ensure_not_locked(
+        currency: currency::Currency,
+        who: &T::AccountId,
+        reasons: WithdrawReasons,
+        new_balance: T::Balance,
+    ) -> DispatchResult {
+        ensure!(
+            new_balance >= Self::frozen_balance(currency, who, reasons),
+            Error::<T>::LiquidityRestrictions
+        );
+        Ok(())
+    }
+
+    /// Stores locks, removing the entry when there are none left
+    fn write_locks(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        locks: Vec<BalanceLock<T::Balance>>,
+    ) {
+        if locks.len() as u32 > T::MaxLocks::get() {
+            eq_warn!(
+                target: LOG_TARGET,
+                "Warning: A user has more currency locks than expected. \
+                A runtime configuration adjustment may be needed."
+            );
+        }
+
+        let existed = <Locks<T>>::contains_key(who, currency);
+        if locks.is_empty() {
+            if existed {
+                <Locks<T>>::remove(who, currency);
+                Self::on_entry_removed(who);
+            }
+        } else {
+            if !existed {
+                Self::on_entry_created(who);
+            }
+            <Locks<T>>::insert(who, currency, locks);
+        }
+    }
+}
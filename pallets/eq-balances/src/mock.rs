@@ -2,14 +2,28 @@
 
 use super::*;
 
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use eq_primitives::asset::AssetData;
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, weights::Weight};
+use sp_arithmetic::FixedPointNumber;
 use sp_core::H256;
 use sp_runtime::{testing::Header, traits::IdentityLookup, Perbill};
+use std::cell::RefCell;
 
 impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod eq_balances {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        eq_balances<T>,
+    }
+}
+
 // For testing the module, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of modules we want to use.
@@ -27,6 +41,12 @@ parameter_types! {
     pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(16);
     pub const TotalIssuence:u64 = 1_000_000_000;
     pub const ExistentialDeposit: u64 = 1;
+    pub const MaxReserves: u32 = 2;
+    pub const MaxLocks: u32 = 50;
+    pub const CheckpointPeriod: u64 = 10;
+    pub const CheckpointRetention: u64 = 30;
+    pub const MaxCheckpointsPruned: u32 = 1;
+    pub const EthCurrencyGet: currency::Currency = currency::Currency::Eth;
 }
 
 type DummyValidatorId = u64;
@@ -42,7 +62,7 @@ impl system::Trait for Test {
     type AccountId = DummyValidatorId;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type BlockHashCount = BlockHashCount;
     type MaximumBlockWeight = MaximumBlockWeight;
     type MaximumExtrinsicWeight = MaximumBlockWeight;
@@ -59,17 +79,136 @@ impl system::Trait for Test {
     type SystemWeightInfo = ();
 }
 
+pub const TREASURY_ACCOUNT_ID: u64 = 1000;
+
+pub struct TreasuryAccountGetter;
+impl eq_primitives::AccountGetter<u64> for TreasuryAccountGetter {
+    fn get_account_id() -> u64 {
+        TREASURY_ACCOUNT_ID
+    }
+}
+
+thread_local! {
+    static ASSETS: RefCell<Vec<(currency::Currency, AssetData<u64>)>> = RefCell::new(vec![]);
+}
+
+/// Registry with the built-in currencies unlisted, listed assets are set by tests
+pub struct AssetGetterMock;
+impl AssetGetterMock {
+    pub fn set_asset(currency: currency::Currency, asset: AssetData<u64>) {
+        ASSETS.with(|assets| {
+            let mut assets = assets.borrow_mut();
+            assets.retain(|(c, _)| *c != currency);
+            assets.push((currency, asset));
+        });
+    }
+}
+impl AssetGetter<u64> for AssetGetterMock {
+    fn get_asset(currency: &currency::Currency) -> Option<AssetData<u64>> {
+        ASSETS.with(|assets| {
+            assets
+                .borrow()
+                .iter()
+                .find(|(c, _)| c == currency)
+                .map(|(_, a)| a.clone())
+        })
+    }
+
+    fn currencies() -> Vec<currency::Currency> {
+        let mut currencies = <() as AssetGetter<u64>>::currencies();
+        ASSETS.with(|assets| {
+            for (currency, _) in assets.borrow().iter() {
+                if !currencies.contains(currency) {
+                    currencies.push(*currency);
+                }
+            }
+        });
+        currencies
+    }
+}
+
+thread_local! {
+    static RESTRICTED: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static COLLATERALIZED: RefCell<Vec<u64>> = RefCell::new(vec![]);
+}
+
+/// Rejects decreasing balances of the restricted accounts and debt of the collateralized
+/// accounts that is not covered by their positive balances, all currencies are priced
+/// equally
+pub struct BalanceCheckerMock;
+impl BalanceCheckerMock {
+    pub fn restrict(who: u64) {
+        RESTRICTED.with(|restricted| restricted.borrow_mut().push(who));
+    }
+
+    pub fn require_collateral(who: u64) {
+        COLLATERALIZED.with(|collateralized| collateralized.borrow_mut().push(who));
+    }
+}
+impl BalanceChecker<u64, u64> for BalanceCheckerMock {
+    fn can_change_balance(
+        who: &u64,
+        currency: &currency::Currency,
+        change: &SignedBalance<u64>,
+    ) -> DispatchResult {
+        let value = match change {
+            SignedBalance::Positive(_) => return Ok(()),
+            SignedBalance::Negative(value) => *value,
+        };
+        if RESTRICTED.with(|restricted| restricted.borrow().contains(who)) {
+            return Err(Error::<Test>::LiquidityRestrictions.into());
+        }
+        if COLLATERALIZED.with(|collateralized| collateralized.borrow().contains(who)) {
+            let mut balances = ModuleBalances::balances_of(who);
+            balances.push((*currency, SignedBalance::Negative(value)));
+            let (collateral, debt) = balances.iter().fold(
+                (0u64, 0u64),
+                |(collateral, debt), (_, balance)| match balance {
+                    SignedBalance::Positive(p) => (collateral + p, debt),
+                    SignedBalance::Negative(n) => (collateral, debt + n),
+                },
+            );
+            ensure!(collateral >= debt, Error::<Test>::LiquidityRestrictions);
+        }
+        Ok(())
+    }
+}
+
 impl Trait for Test {
     type Balance = u64;
+    type Amount = i64;
     type TotalIssuance = TotalIssuence;
     type ExistentialDeposit = ExistentialDeposit;
-    type BalanceChecker = ();
-    type Event = ();
+    type TreasuryAccountGetter = TreasuryAccountGetter;
+    type MaxReserves = MaxReserves;
+    type MaxLocks = MaxLocks;
+    type FreezeOrigin = system::EnsureRoot<u64>;
+    type CheckpointPeriod = CheckpointPeriod;
+    type CheckpointRetention = CheckpointRetention;
+    type MaxCheckpointsPruned = MaxCheckpointsPruned;
+    type BalanceChecker = BalanceCheckerMock;
+    type Event = TestEvent;
     type BalanceGetter = ModuleBalances;
+    type AssetGetter = AssetGetterMock;
     type WeightInfo = ();
 }
 
+pub struct PriceGetterMock;
+impl credit_limit::PriceGetter for PriceGetterMock {
+    fn get_price(currency: &currency::Currency) -> Result<FixedU128, DispatchError> {
+        match currency {
+            currency::Currency::Usd => Ok(FixedU128::saturating_from_integer(1)),
+            currency::Currency::Eq => Ok(FixedU128::saturating_from_rational(1, 4)),
+            currency::Currency::Eth => Ok(FixedU128::saturating_from_integer(250)),
+            currency::Currency::Btc => Ok(FixedU128::saturating_from_integer(10_000)),
+            _ => Err(DispatchError::Other("no price")),
+        }
+    }
+}
+
 pub type ModuleBalances = Module<Test>;
+pub type System = system::Module<Test>;
+pub type EthAdapter = balance_adapter::BalanceAdapter<Test, ModuleBalances, EthCurrencyGet>;
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
     let mut t = system::GenesisConfig::default()
@@ -78,15 +217,24 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 
     GenesisConfig::<Test> {
         balances: vec![
-            (1, 1000_000_000_000 as u64, currency::Currency::Btc.value()),
-            (2, 2000_000_000_000 as u64, currency::Currency::Btc.value()),
-            (10, 10_000_000_000 as u64, currency::Currency::Usd.value()),
-            (20, 20_000_000_000 as u64, currency::Currency::Usd.value()),
-            (30, 30_000_000_000 as u64, currency::Currency::Usd.value()),
+            (1, currency::Currency::Btc, Positive(1000_000_000_000)),
+            (2, currency::Currency::Btc, Positive(2000_000_000_000)),
+            (10, currency::Currency::Usd, Positive(10_000_000_000)),
+            (20, currency::Currency::Usd, Positive(20_000_000_000)),
+            (30, currency::Currency::Usd, Positive(30_000_000_000)),
         ],
     }
     .assimilate_storage(&mut t)
     .unwrap();
 
+    AssetGetterMock::set_asset(
+        currency::Currency::Eth,
+        AssetData {
+            symbol: b"ETH".to_vec(),
+            decimals: 9,
+            existential_deposit: 1_000,
+            status: eq_primitives::asset::AssetStatus::Active,
+        },
+    );
     t.into()
 }
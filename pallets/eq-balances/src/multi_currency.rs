@@ -0,0 +1,237 @@
+//! `orml_traits` multi-currency interface of the module. Funds reserved through it are
+//! kept under `DEFAULT_RESERVE_ID`, same as with `BalanceAdapter`.
+
+use super::*;
+use balance_adapter::DEFAULT_RESERVE_ID;
+use orml_traits::{MultiCurrency, MultiCurrencyExtended, MultiReservableCurrency};
+use sp_runtime::traits::Saturating;
+
+impl<T: Trait> MultiCurrency<T::AccountId> for Module<T> {
+    type CurrencyId = currency::Currency;
+    type Balance = T::Balance;
+
+    fn minimum_balance(currency_id: Self::CurrencyId) -> Self::Balance {
+        <Self as EqCurrency<_, _>>::currency_minimum_balance(currency_id)
+    }
+
+    fn total_issuance(currency_id: Self::CurrencyId) -> Self::Balance {
+        <Self as EqCurrency<_, _>>::currency_total_issuance(currency_id)
+    }
+
+    /// Positive balance together with the `DEFAULT_RESERVE_ID` reserve, same as
+    /// `MultiReservableCurrency::reserved_balance`
+    fn total_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+        <Self as EqCurrency<_, _>>::total_balance(currency_id, who).saturating_add(
+            <Self as EqReservableCurrency<_, _>>::reserved_balance_named(
+                currency_id,
+                &DEFAULT_RESERVE_ID,
+                who,
+            ),
+        )
+    }
+
+    fn free_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+        <Self as EqCurrency<_, _>>::free_balance(currency_id, who)
+    }
+
+    fn ensure_can_withdraw(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        let new_balance = Self::account_balance(who, &currency_id)
+            .sub_balance(amount)
+            .ok_or(Error::<T>::Overflow)?;
+        <Self as EqCurrency<_, _>>::ensure_can_withdraw(
+            currency_id,
+            who,
+            amount,
+            WithdrawReasons::all(),
+            Self::free_balance_of(&new_balance),
+        )
+    }
+
+    fn transfer(
+        currency_id: Self::CurrencyId,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        Self::currency_transfer(
+            currency_id,
+            from,
+            to,
+            amount,
+            ExistenceRequirement::AllowDeath,
+        )
+    }
+
+    /// Creates the balance when the amount reaches the existential deposit
+    fn deposit(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        if amount.is_zero() {
+            return Ok(());
+        }
+        ensure!(
+            !Self::account_balance(who, &currency_id).is_zero()
+                || amount >= Self::currency_minimum_balance(currency_id),
+            Error::<T>::ExistentialDeposit
+        );
+        Self::deposit_into_existing(currency_id, who, amount).map(|_| ())
+    }
+
+    fn withdraw(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        amount: Self::Balance,
+    ) -> DispatchResult {
+        <Self as EqCurrency<_, _>>::withdraw(
+            currency_id,
+            who,
+            amount,
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath,
+        )
+        .map(|_| ())
+    }
+
+    fn can_slash(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> bool {
+        <Self as EqCurrency<_, _>>::can_slash(currency_id, who, value)
+    }
+
+    /// Slashes the balance first and the reserve for the rest, returns the amount that
+    /// could not be slashed
+    fn slash(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        amount: Self::Balance,
+    ) -> Self::Balance {
+        let (_, remaining) = <Self as EqCurrency<_, _>>::slash(currency_id, who, amount);
+        if remaining.is_zero() {
+            return remaining;
+        }
+        let (_, remaining) = <Self as EqReservableCurrency<_, _>>::slash_reserved_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            who,
+            remaining,
+        );
+        remaining
+    }
+}
+
+impl<T: Trait> MultiCurrencyExtended<T::AccountId> for Module<T> {
+    type Amount = T::Amount;
+
+    /// Deposits positive and withdraws negative amounts
+    fn update_balance(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        by_amount: Self::Amount,
+    ) -> DispatchResult {
+        match Self::signed_balance_of(by_amount)? {
+            Positive(value) => <Self as MultiCurrency<_>>::deposit(currency_id, who, value),
+            Negative(value) => <Self as MultiCurrency<_>>::withdraw(currency_id, who, value),
+        }
+    }
+}
+
+impl<T: Trait> MultiReservableCurrency<T::AccountId> for Module<T> {
+    fn can_reserve(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> bool {
+        <Self as EqReservableCurrency<_, _>>::can_reserve(currency_id, who, value)
+    }
+
+    fn slash_reserved(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance {
+        let (_, remaining) = <Self as EqReservableCurrency<_, _>>::slash_reserved_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            who,
+            value,
+        );
+        remaining
+    }
+
+    fn reserved_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+        <Self as EqReservableCurrency<_, _>>::reserved_balance_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            who,
+        )
+    }
+
+    fn reserve(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> DispatchResult {
+        <Self as EqReservableCurrency<_, _>>::reserve_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            who,
+            value,
+        )
+    }
+
+    fn unreserve(
+        currency_id: Self::CurrencyId,
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance {
+        <Self as EqReservableCurrency<_, _>>::unreserve_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            who,
+            value,
+        )
+    }
+
+    fn repatriate_reserved(
+        currency_id: Self::CurrencyId,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> result::Result<Self::Balance, DispatchError> {
+        <Self as EqReservableCurrency<_, _>>::repatriate_reserved_named(
+            currency_id,
+            &DEFAULT_RESERVE_ID,
+            slashed,
+            beneficiary,
+            value,
+            status,
+        )
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Signed balance of the amount, fails when it doesn't fit the balance type
+    fn signed_balance_of(amount: T::Amount) -> Result<SignedBalance<T::Balance>, DispatchError> {
+        ensure!(
+            amount != T::Amount::min_value(),
+            Error::<T>::AmountIntoBalanceFailed
+        );
+        let value: T::Balance = amount
+            .abs()
+            .try_into()
+            .map_err(|_| Error::<T>::AmountIntoBalanceFailed)?;
+        if amount.is_negative() {
+            Ok(Negative(value))
+        } else {
+            Ok(Positive(value))
+        }
+    }
+}
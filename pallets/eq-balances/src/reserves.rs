@@ -0,0 +1,385 @@
+use super::*;
+use sp_runtime::traits::{CheckedAdd, Saturating};
+use sp_runtime::RuntimeDebug;
+
+/// Identifier of a named reserve
+pub type ReserveIdentifier = [u8; 8];
+
+/// Amount reserved under a specific identifier
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct ReserveData<Balance> {
+    pub id: ReserveIdentifier,
+    pub amount: Balance,
+}
+
+/// Manages funds put on hold under named reserves in different currencies
+pub trait EqReservableCurrency<AccountId, Balance>: EqCurrency<AccountId, Balance>
+where
+    Balance: Member
+        + AtLeast32BitUnsigned
+        + FullCodec
+        + Copy
+        + MaybeSerializeDeserialize
+        + Debug
+        + Default,
+{
+    fn can_reserve(currency: currency::Currency, who: &AccountId, value: Balance) -> bool;
+    fn reserved_balance(currency: currency::Currency, who: &AccountId) -> Balance;
+    fn reserved_balance_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &AccountId,
+    ) -> Balance;
+    fn reserve_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Balance,
+    ) -> DispatchResult;
+    fn unreserve_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Balance,
+    ) -> Balance;
+    fn slash_reserved_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Balance,
+    ) -> (Self::NegativeImbalance, Balance);
+    fn repatriate_reserved_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        value: Balance,
+        status: BalanceStatus,
+    ) -> Result<Balance, DispatchError>;
+}
+
+impl<T: Trait> EqReservableCurrency<T::AccountId, T::Balance> for Module<T> {
+    fn can_reserve(currency: currency::Currency, who: &T::AccountId, value: T::Balance) -> bool {
+        if value.is_zero() {
+            return true;
+        }
+        Self::free_balance(currency, who) >= value
+            && !Self::is_frozen(who, &currency)
+            && T::BalanceChecker::can_change_balance(
+                &who,
+                &currency,
+                &SignedBalance::Negative(value),
+            )
+            .is_ok()
+    }
+
+    fn reserved_balance(currency: currency::Currency, who: &T::AccountId) -> T::Balance {
+        Self::reserves_total(&<Reserves<T>>::get(who, &currency))
+    }
+
+    fn reserved_balance_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+    ) -> T::Balance {
+        let reserves = <Reserves<T>>::get(who, &currency);
+        match reserves.binary_search_by_key(id, |reserve| reserve.id) {
+            Ok(index) => reserves[index].amount,
+            Err(_) => T::Balance::zero(),
+        }
+    }
+
+    /// Moves `value` from the account balance to the named reserve. Only positive balance
+    /// can be reserved, so reserving never creates debt.
+    fn reserve_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> DispatchResult {
+        if value.is_zero() {
+            return Ok(());
+        }
+
+        Self::ensure_currency_active(&currency)?;
+        Self::ensure_not_frozen(who, &currency)?;
+        ensure!(
+            Self::free_balance(currency, who) >= value,
+            Error::<T>::InsufficientBalance
+        );
+        T::BalanceChecker::can_change_balance(&who, &currency, &SignedBalance::Negative(value))?;
+        Self::ensure_not_locked(
+            currency,
+            who,
+            WithdrawReason::Reserve.into(),
+            Self::free_balance(currency, who) - value,
+        )?;
+
+        let mut reserves = <Reserves<T>>::get(who, &currency);
+        Self::add_to_reserves(&mut reserves, id, value)?;
+
+        let account = Self::account_balance(who, &currency);
+        let new_account = account.sub_balance(value).ok_or(Error::<T>::Overflow)?;
+
+        let aggregate =
+            Self::aggregate_with_reserved(who, &currency, &account, &new_account, Positive(value))?;
+        <BalancesAggregates<T>>::insert(&currency, aggregate);
+        // reserves are stored first, so reserving the whole balance doesn't kill the account
+        Self::write_reserves(who, &currency, reserves);
+        Self::write_balance(who, &currency, new_account);
+
+        Self::deposit_event(RawEvent::Reserved(who.clone(), currency, *id, value));
+        Ok(())
+    }
+
+    /// Moves up to `value` from the named reserve back to the account balance. Returns the
+    /// amount that could not be unreserved.
+    fn unreserve_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> T::Balance {
+        if value.is_zero() {
+            return T::Balance::zero();
+        }
+
+        let mut reserves = <Reserves<T>>::get(who, &currency);
+        let actual = Self::remove_from_reserves(&mut reserves, id, value);
+        if actual.is_zero() {
+            return value;
+        }
+
+        let account = Self::account_balance(who, &currency);
+        let new_account = match account.add_balance(actual) {
+            Some(new_account) => new_account,
+            None => return value,
+        };
+
+        if let Err(e) = Self::update_balance_with_reserved(
+            who,
+            &currency,
+            &account,
+            new_account,
+            Negative(actual),
+        ) {
+            eq_error!(target: LOG_TARGET, "failed to unreserve {:?}: {:?}", who, e);
+            return value;
+        }
+        Self::write_reserves(who, &currency, reserves);
+
+        Self::deposit_event(RawEvent::Unreserved(who.clone(), currency, *id, actual));
+        value - actual
+    }
+
+    fn slash_reserved_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> (NegativeImbalance<T>, T::Balance) {
+        if value.is_zero() {
+            return (NegativeImbalance::zero(), T::Balance::zero());
+        }
+
+        let mut reserves = <Reserves<T>>::get(who, &currency);
+        let actual = Self::remove_from_reserves(&mut reserves, id, value);
+        if actual.is_zero() {
+            return (NegativeImbalance::zero(), value);
+        }
+
+        Self::write_reserves(who, &currency, reserves);
+
+        Self::deposit_event(RawEvent::ReserveSlashed(who.clone(), currency, *id, actual));
+        (NegativeImbalance::new(currency, actual), value - actual)
+    }
+
+    /// Moves up to `value` from the named reserve of `slashed` to `beneficiary`. With
+    /// `BalanceStatus::Reserved` funds are put to the reserve of `beneficiary` with the same
+    /// identifier. Returns the amount that could not be moved.
+    fn repatriate_reserved_named(
+        currency: currency::Currency,
+        id: &ReserveIdentifier,
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        value: T::Balance,
+        status: BalanceStatus,
+    ) -> Result<T::Balance, DispatchError> {
+        if value.is_zero() {
+            return Ok(T::Balance::zero());
+        }
+
+        if slashed == beneficiary {
+            return match status {
+                BalanceStatus::Free => Ok(Self::unreserve_named(currency, id, slashed, value)),
+                BalanceStatus::Reserved => {
+                    Ok(value - value.min(Self::reserved_balance_named(currency, id, slashed)))
+                }
+            };
+        }
+
+        let mut reserves = <Reserves<T>>::get(slashed, &currency);
+        let actual = Self::remove_from_reserves(&mut reserves, id, value);
+        if actual.is_zero() {
+            return Ok(value);
+        }
+
+        match status {
+            BalanceStatus::Free => {
+                T::BalanceChecker::can_change_balance(
+                    &beneficiary,
+                    &currency,
+                    &SignedBalance::Positive(actual),
+                )?;
+                let to_account = Self::account_balance(beneficiary, &currency);
+                let new_to_account = to_account.add_balance(actual).ok_or(Error::<T>::Overflow)?;
+
+                Self::update_balance_with_reserved(
+                    beneficiary,
+                    &currency,
+                    &to_account,
+                    new_to_account,
+                    Negative(actual),
+                )?;
+            }
+            BalanceStatus::Reserved => {
+                let mut to_reserves = <Reserves<T>>::get(beneficiary, &currency);
+                Self::add_to_reserves(&mut to_reserves, id, actual)?;
+
+                Self::write_reserves(beneficiary, &currency, to_reserves);
+            }
+        }
+        Self::write_reserves(slashed, &currency, reserves);
+
+        Self::deposit_event(RawEvent::ReserveRepatriated(
+            slashed.clone(),
+            beneficiary.clone(),
+            currency,
+            *id,
+            actual,
+            status,
+        ));
+        Ok(value - actual)
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// Stores reserves, removing the entry when there are none left
+    fn write_reserves(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        reserves: Vec<ReserveData<T::Balance>>,
+    ) {
+        let old_reserves = <Reserves<T>>::get(who, currency);
+        let existed = !old_reserves.is_empty();
+        Self::track_repair(
+            &<Reserves<T>>::hashed_key_for(who, currency),
+            currency,
+            &Positive(Self::reserves_total(&old_reserves)),
+            &Positive(Self::reserves_total(&reserves)),
+        );
+        if reserves.is_empty() {
+            if existed {
+                <Reserves<T>>::remove(who, currency);
+                Self::on_entry_removed(who);
+            }
+        } else {
+            if !existed {
+                Self::on_entry_created(who);
+            }
+            <Reserves<T>>::insert(who, currency, reserves);
+        }
+    }
+
+    fn reserves_total(reserves: &[ReserveData<T::Balance>]) -> T::Balance {
+        reserves.iter().fold(T::Balance::zero(), |total, reserve| {
+            total.saturating_add(reserve.amount)
+        })
+    }
+
+    /// Reserves are kept sorted by identifier
+    fn add_to_reserves(
+        reserves: &mut Vec<ReserveData<T::Balance>>,
+        id: &ReserveIdentifier,
+        value: T::Balance,
+    ) -> DispatchResult {
+        match reserves.binary_search_by_key(id, |reserve| reserve.id) {
+            Ok(index) => {
+                reserves[index].amount = reserves[index]
+                    .amount
+                    .checked_add(&value)
+                    .ok_or(Error::<T>::Overflow)?;
+            }
+            Err(index) => {
+                ensure!(
+                    (reserves.len() as u32) < T::MaxReserves::get(),
+                    Error::<T>::TooManyReserves
+                );
+                reserves.insert(
+                    index,
+                    ReserveData {
+                        id: *id,
+                        amount: value,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes up to `value` from the reserve, returns the amount actually removed
+    fn remove_from_reserves(
+        reserves: &mut Vec<ReserveData<T::Balance>>,
+        id: &ReserveIdentifier,
+        value: T::Balance,
+    ) -> T::Balance {
+        match reserves.binary_search_by_key(id, |reserve| reserve.id) {
+            Ok(index) => {
+                let actual = value.min(reserves[index].amount);
+                reserves[index].amount -= actual;
+                if reserves[index].amount.is_zero() {
+                    reserves.remove(index);
+                }
+                actual
+            }
+            Err(_) => T::Balance::zero(),
+        }
+    }
+
+    /// Updates the account balance together with its reserved funds, `reserved` is the
+    /// change of reserves. Aggregates are checked before anything is written, reserves
+    /// themselves are written by the caller after the balance.
+    fn update_balance_with_reserved(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        old_balance: &SignedBalance<T::Balance>,
+        new_balance: SignedBalance<T::Balance>,
+        reserved: SignedBalance<T::Balance>,
+    ) -> DispatchResult {
+        let aggregate =
+            Self::aggregate_with_reserved(who, currency, old_balance, &new_balance, reserved)?;
+        <BalancesAggregates<T>>::insert(currency, aggregate);
+        Self::write_balance(who, currency, new_balance);
+        Ok(())
+    }
+
+    /// Aggregate of the currency after the balance change and `reserved` change of reserves
+    fn aggregate_with_reserved(
+        who: &T::AccountId,
+        currency: &currency::Currency,
+        old_balance: &SignedBalance<T::Balance>,
+        new_balance: &SignedBalance<T::Balance>,
+        reserved: SignedBalance<T::Balance>,
+    ) -> Result<BalancesAggregate<T::Balance>, DispatchError> {
+        let aggregate =
+            Self::aggregate_after(currency, &[(who, old_balance.clone(), new_balance.clone())])?;
+        match reserved {
+            Positive(amount) => aggregate
+                .checked_add(&Positive(amount))
+                .ok_or_else(|| Error::<T>::Overflow.into()),
+            Negative(amount) => aggregate
+                .checked_sub(&Positive(amount))
+                .ok_or_else(|| Error::<T>::AggregatesUnderflow.into()),
+        }
+    }
+}
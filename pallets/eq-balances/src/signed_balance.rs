@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use sp_std::fmt::Debug;
 
 /// Balance that supports negative values
-#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum SignedBalance<Balance>
 where
@@ -1,9 +1,16 @@
 #![cfg(test)]
 
 use super::*;
-use crate::mock::{new_test_ext, ModuleBalances, Origin};
-use frame_support::assert_ok;
-use frame_support::traits::{WithdrawReason, WithdrawReasons};
+use crate::mock::{
+    new_test_ext, AssetGetterMock, BalanceCheckerMock, EthAdapter, ModuleBalances, Origin, System,
+    Test, TestEvent, TREASURY_ACCOUNT_ID,
+};
+use eq_primitives::asset::AssetData;
+use frame_support::traits::{
+    Currency as _, LockIdentifier, LockableCurrency, OnInitialize, ReservableCurrency,
+    WithdrawReason, WithdrawReasons,
+};
+use frame_support::{assert_noop, assert_ok};
 
 #[test]
 fn no_balances() {
@@ -263,3 +270,1886 @@ fn test_ensure_can_withdraw_and_withdraw() {
         });
     });
 }
+
+#[test]
+fn test_can_slash() {
+    new_test_ext().execute_with(|| {
+        let account_id_1: u64 = 1;
+        let account_id_10: u64 = 10;
+
+        assert!(ModuleBalances::can_slash(Btc, &account_id_1, 0));
+        assert!(ModuleBalances::can_slash(
+            Btc,
+            &account_id_1,
+            1000_000_000_000
+        ));
+        assert!(!ModuleBalances::can_slash(
+            Btc,
+            &account_id_1,
+            1000_000_000_001
+        ));
+
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(account_id_10),
+            Usd,
+            account_id_1,
+            15_000_000_000
+        ));
+        assert!(ModuleBalances::can_slash(Usd, &account_id_10, 0));
+        assert!(!ModuleBalances::can_slash(Usd, &account_id_10, 1));
+    });
+}
+
+#[test]
+fn test_slash() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+        let account_id_1: u64 = 1;
+        let account_id_2: u64 = 2;
+
+        let (imbalance, remaining) = ModuleBalances::slash(Btc, &account_id_1, 400_000_000_000);
+        assert_eq!(imbalance.peek(), 400_000_000_000);
+        assert_eq!(remaining, 0);
+        drop(imbalance);
+        check_balance_and_debt(&account_id_1, 600_000_000_000, 0, Btc, line!());
+        check_balances_aggregates(Btc, 2600_000_000_000, 0, line!());
+
+        let (imbalance, remaining) = ModuleBalances::slash(Btc, &account_id_2, 2500_000_000_000);
+        assert_eq!(imbalance.peek(), 2000_000_000_000);
+        assert_eq!(remaining, 500_000_000_000);
+        drop(imbalance);
+        check_balance_and_debt(&account_id_2, 0, 0, Btc, line!());
+        check_balances_aggregates(Btc, 600_000_000_000, 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_slash_respects_balance_checker() {
+    new_test_ext().execute_with(|| {
+        BalanceCheckerMock::restrict(1);
+        assert!(!ModuleBalances::can_slash(Btc, &1, 100));
+
+        let (imbalance, remaining) = ModuleBalances::slash(Btc, &1, 100);
+        assert_eq!(imbalance.peek(), 0);
+        assert_eq!(remaining, 100);
+        assert_eq!(ModuleBalances::free_balance(Btc, &1), 1000_000_000_000);
+    });
+}
+
+#[test]
+fn test_slash_negative_balance() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+        let account_id_10: u64 = 10;
+        let account_id_20: u64 = 20;
+
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(account_id_10),
+            Usd,
+            account_id_20,
+            15_000_000_000
+        ));
+        check_balance_and_debt(&account_id_10, 0, 5_000_000_000, Usd, line!());
+
+        let (imbalance, remaining) = ModuleBalances::slash(Usd, &account_id_10, 1_000_000_000);
+        assert_eq!(imbalance.peek(), 0);
+        assert_eq!(remaining, 1_000_000_000);
+        check_balance_and_debt(&account_id_10, 0, 5_000_000_000, Usd, line!());
+        check_balances_aggregates(Usd, 65_000_000_000, 5_000_000_000, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_burn_and_issue() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        let imbalance = <ModuleBalances as EqCurrency<u64, u64>>::burn(Btc, 1000_000_000_000);
+        assert_eq!(imbalance.peek(), 1000_000_000_000);
+        check_balances_aggregates(Btc, 2000_000_000_000, 0, line!());
+
+        let imbalance = <ModuleBalances as EqCurrency<u64, u64>>::burn(Btc, 3000_000_000_000);
+        assert_eq!(imbalance.peek(), 2000_000_000_000);
+        check_balances_aggregates(Btc, 0, 0, line!());
+
+        let imbalance = ModuleBalances::issue(Eos, 500);
+        assert_eq!(imbalance.peek(), 500);
+        check_balances_aggregates(Eos, 500, 0, line!());
+
+        let imbalance = ModuleBalances::issue(Eos, u64::max_value());
+        assert_eq!(imbalance.peek(), u64::max_value() - 500);
+        check_balances_aggregates(Eos, u64::max_value(), 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_imbalances_settle_on_drop() {
+    new_test_ext().execute_with(|| {
+        let imbalance = ModuleBalances::deposit_creating(Btc, &5, 100);
+        assert_eq!(imbalance.currency(), Btc);
+        check_balance_and_debt(&5, 100, 0, Btc, line!());
+        check_balances_aggregates(Btc, 3000_000_000_000, 0, line!());
+        drop(imbalance);
+        check_balances_aggregates(Btc, 3000_000_000_100, 0, line!());
+
+        let withdrawn = ModuleBalances::withdraw(
+            Btc,
+            &5,
+            40,
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath,
+        )
+        .unwrap();
+        check_balances_aggregates(Btc, 3000_000_000_100, 0, line!());
+        ModuleBalances::resolve_creating(Btc, &6, withdrawn);
+        check_balance_and_debt(&6, 40, 0, Btc, line!());
+        check_balances_aggregates(Btc, 3000_000_000_100, 0, line!());
+
+        let positive = PositiveImbalance::<Test>::new(Usd, 10);
+        let negative = NegativeImbalance::<Test>::new(Usd, 30);
+        let remaining = positive.offset(negative).err().unwrap();
+        assert_eq!(remaining.currency(), Usd);
+        assert_eq!(remaining.peek(), 20);
+        drop(remaining);
+        check_balances_aggregates(Usd, 59_999_999_980, 0, line!());
+    });
+}
+
+#[test]
+fn test_imbalances_of_different_currencies() {
+    new_test_ext().execute_with(|| {
+        let btc = PositiveImbalance::<Test>::new(Btc, 10);
+        let usd = PositiveImbalance::<Test>::new(Usd, 20);
+        let (btc, usd) = btc.try_merge(usd).err().unwrap();
+
+        let merged = btc.merge(PositiveImbalance::zero());
+        assert_eq!(merged.currency(), Btc);
+        assert_eq!(merged.peek(), 10);
+
+        let merged = PositiveImbalance::zero().merge(usd);
+        assert_eq!(merged.currency(), Usd);
+        assert_eq!(merged.peek(), 20);
+
+        let negative = NegativeImbalance::<Test>::new(Btc, 5);
+        let (merged, negative) = merged.try_offset(negative).err().unwrap();
+        assert_eq!(merged.peek(), 20);
+
+        drop(merged);
+        drop(negative);
+        check_balances_aggregates(Btc, 2999_999_999_995, 0, line!());
+        check_balances_aggregates(Usd, 60_000_000_020, 0, line!());
+    });
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "merge of imbalances of different currencies")]
+fn test_merge_of_different_currencies_fails() {
+    new_test_ext().execute_with(|| {
+        let btc = PositiveImbalance::<Test>::new(Btc, 10);
+        let usd = PositiveImbalance::<Test>::new(Usd, 20);
+        let _ = btc.merge(usd);
+    });
+}
+
+#[test]
+fn test_make_free_balance_be_leaves_aggregates_on_failure() {
+    new_test_ext().execute_with(|| {
+        BalanceCheckerMock::restrict(10);
+        let imbalance = ModuleBalances::make_free_balance_be(Usd, &10, 1_000);
+        assert!(matches!(imbalance, SignedImbalance::Positive(ref i) if i.peek() == 0));
+        drop(imbalance);
+        check_balance_and_debt(&10, 10_000_000_000, 0, Usd, line!());
+        check_balances_aggregates(Usd, 60_000_000_000, 0, line!());
+
+        ModuleBalances::set_balance_unsafe(&5, &Usd, Negative(u64::max_value()));
+        <BalancesAggregates<Test>>::mutate(Usd, |aggregate| {
+            aggregate.total_debt = u64::max_value()
+        });
+        let imbalance = ModuleBalances::make_free_balance_be(Usd, &5, 1);
+        assert!(matches!(imbalance, SignedImbalance::Positive(ref i) if i.peek() == 0));
+        drop(imbalance);
+        check_balance_and_debt(&5, 0, u64::max_value(), Usd, line!());
+        check_balances_aggregates(Usd, 60_000_000_000, u64::max_value(), line!());
+    });
+}
+
+#[test]
+fn test_existential_deposit() {
+    new_test_ext().execute_with(|| {
+        // unlisted currencies use `T::ExistentialDeposit`
+        assert_eq!(ModuleBalances::currency_minimum_balance(Btc), 1);
+        assert_eq!(ModuleBalances::currency_minimum_balance(Eth), 1_000);
+    });
+}
+
+#[test]
+fn test_transfer_below_existential_deposit_to_new_account() {
+    new_test_ext().execute_with(|| {
+        ModuleBalances::deposit_creating(Eth, &1, 10_000);
+
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Eth, 2, 999),
+            Error::<Test>::ExistentialDeposit
+        );
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Eth, 2, 1_000));
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Eth, 2, 1));
+    });
+}
+
+#[test]
+fn test_dust_collected_to_treasury() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        ModuleBalances::deposit_creating(Eth, &1, 10_000);
+        assert_noop!(
+            ModuleBalances::currency_transfer(Eth, &1, &2, 9_500, ExistenceRequirement::KeepAlive),
+            Error::<Test>::KeepAlive
+        );
+        assert_ok!(ModuleBalances::currency_transfer(
+            Eth,
+            &1,
+            &2,
+            9_500,
+            ExistenceRequirement::AllowDeath
+        ));
+
+        check_balance_and_debt(&1, 0, 0, Eth, line!());
+        check_balance_and_debt(&2, 9_500, 0, Eth, line!());
+        check_balance_and_debt(&TREASURY_ACCOUNT_ID, 500, 0, Eth, line!());
+        check_balances_aggregates(Eth, 10_000, 0, line!());
+        assert!(Accounts::<Test>::get(1).balance(&Eth).is_zero());
+
+        assert_noop!(
+            ModuleBalances::withdraw(
+                Eth,
+                &2,
+                9_000,
+                WithdrawReasons::all(),
+                ExistenceRequirement::KeepAlive
+            ),
+            Error::<Test>::KeepAlive
+        );
+        assert_ok!(ModuleBalances::withdraw(
+            Eth,
+            &2,
+            9_000,
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath
+        ));
+        check_balance_and_debt(&2, 0, 0, Eth, line!());
+        check_balance_and_debt(&TREASURY_ACCOUNT_ID, 1_000, 0, Eth, line!());
+        check_balances_aggregates(Eth, 1_000, 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_account_reaped_in_system() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+        assert_eq!(system::Module::<Test>::refs(&3), 0);
+
+        ModuleBalances::deposit_creating(Eth, &1, 10_000);
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(1),
+            Btc,
+            3,
+            1000_000_000_000
+        ));
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+        assert_eq!(system::Module::<Test>::refs(&3), 1);
+
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Eth, 3, 10_000));
+        assert_eq!(system::Module::<Test>::refs(&1), 0);
+        assert!(!system::Account::<Test>::contains_key(&1));
+    });
+}
+
+const RESERVE_ID_1: ReserveIdentifier = *b"reserve1";
+const RESERVE_ID_2: ReserveIdentifier = *b"reserve2";
+const RESERVE_ID_3: ReserveIdentifier = *b"reserve3";
+
+#[test]
+fn test_reserve_and_unreserve() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        assert!(ModuleBalances::can_reserve(Btc, &1, 1000_000_000_000));
+        assert!(!ModuleBalances::can_reserve(Btc, &1, 1000_000_000_001));
+
+        assert_ok!(ModuleBalances::reserve_named(
+            Btc,
+            &RESERVE_ID_1,
+            &1,
+            400_000_000_000
+        ));
+        assert_noop!(
+            ModuleBalances::reserve_named(Btc, &RESERVE_ID_2, &1, 600_000_000_001),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_ok!(ModuleBalances::reserve_named(
+            Btc,
+            &RESERVE_ID_2,
+            &1,
+            100_000_000_000
+        ));
+
+        check_balance_and_debt(&1, 500_000_000_000, 0, Btc, line!());
+        assert_eq!(
+            ModuleBalances::reserved_balance_named(Btc, &RESERVE_ID_1, &1),
+            400_000_000_000
+        );
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &1), 500_000_000_000);
+        check_balances_aggregates(Btc, 3000_000_000_000, 0, line!());
+
+        assert_eq!(
+            ModuleBalances::unreserve_named(Btc, &RESERVE_ID_1, &1, 500_000_000_000),
+            100_000_000_000
+        );
+        check_balance_and_debt(&1, 900_000_000_000, 0, Btc, line!());
+        assert_eq!(
+            ModuleBalances::reserved_balance_named(Btc, &RESERVE_ID_1, &1),
+            0
+        );
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &1), 100_000_000_000);
+        check_balances_aggregates(Btc, 3000_000_000_000, 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_too_many_reserves() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 1));
+        assert_ok!(ModuleBalances::reserve_named(Btc, &RESERVE_ID_2, &1, 1));
+        assert_noop!(
+            ModuleBalances::reserve_named(Btc, &RESERVE_ID_3, &1, 1),
+            Error::<Test>::TooManyReserves
+        );
+        assert_ok!(ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 1));
+    });
+}
+
+#[test]
+fn test_reserve_keeps_account_alive() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        System::inc_account_nonce(&1);
+
+        assert_ok!(ModuleBalances::reserve_named(
+            Btc,
+            &RESERVE_ID_1,
+            &1,
+            1000_000_000_000
+        ));
+        assert!(!Accounts::<Test>::contains_key(1));
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+        assert_eq!(System::account_nonce(&1), 1);
+        assert!(System::events()
+            .iter()
+            .all(|r| r.event != TestEvent::system(system::RawEvent::KilledAccount(1))));
+
+        assert_eq!(
+            ModuleBalances::unreserve_named(Btc, &RESERVE_ID_1, &1, 1000_000_000_000),
+            0
+        );
+        assert!(!Reserves::<Test>::contains_key(1, Btc));
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+    });
+}
+
+#[test]
+fn test_slash_reserved() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        assert_ok!(ModuleBalances::reserve_named(
+            Btc,
+            &RESERVE_ID_1,
+            &1,
+            400_000_000_000
+        ));
+        let (imbalance, remaining) =
+            ModuleBalances::slash_reserved_named(Btc, &RESERVE_ID_1, &1, 500_000_000_000);
+        assert_eq!(imbalance.peek(), 400_000_000_000);
+        assert_eq!(remaining, 100_000_000_000);
+        drop(imbalance);
+
+        check_balance_and_debt(&1, 600_000_000_000, 0, Btc, line!());
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &1), 0);
+        check_balances_aggregates(Btc, 2600_000_000_000, 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_repatriate_reserved() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        assert_ok!(ModuleBalances::reserve_named(
+            Btc,
+            &RESERVE_ID_1,
+            &1,
+            400_000_000_000
+        ));
+        assert_eq!(
+            ModuleBalances::repatriate_reserved_named(
+                Btc,
+                &RESERVE_ID_1,
+                &1,
+                &3,
+                300_000_000_000,
+                BalanceStatus::Free
+            ),
+            Ok(0)
+        );
+        check_balance_and_debt(&3, 300_000_000_000, 0, Btc, line!());
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &1), 100_000_000_000);
+        check_balances_aggregates(Btc, 3000_000_000_000, 0, line!());
+
+        assert_eq!(
+            ModuleBalances::repatriate_reserved_named(
+                Btc,
+                &RESERVE_ID_1,
+                &1,
+                &3,
+                200_000_000_000,
+                BalanceStatus::Reserved
+            ),
+            Ok(100_000_000_000)
+        );
+        check_balance_and_debt(&3, 300_000_000_000, 0, Btc, line!());
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &1), 0);
+        assert_eq!(
+            ModuleBalances::reserved_balance_named(Btc, &RESERVE_ID_1, &3),
+            100_000_000_000
+        );
+        check_balances_aggregates(Btc, 3000_000_000_000, 0, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_reservable_currency_adapter() {
+    new_test_ext().execute_with(|| {
+        let _ = EthAdapter::deposit_creating(&5, 10_000);
+
+        assert!(EthAdapter::can_reserve(&5, 10_000));
+        assert_ok!(EthAdapter::reserve(&5, 6_000));
+        assert_eq!(EthAdapter::free_balance(&5), 4_000);
+        assert_eq!(EthAdapter::total_balance(&5), 10_000);
+        assert_eq!(EthAdapter::reserved_balance(&5), 6_000);
+        assert_eq!(
+            ModuleBalances::reserved_balance_named(Eth, &balance_adapter::DEFAULT_RESERVE_ID, &5),
+            6_000
+        );
+
+        assert_eq!(EthAdapter::unreserve(&5, 1_000), 0);
+        assert_eq!(EthAdapter::free_balance(&5), 5_000);
+        assert_eq!(EthAdapter::reserved_balance(&5), 5_000);
+
+        // named reserves are outside of both the reserved and the total balance
+        assert_ok!(ModuleBalances::reserve_named(Eth, &RESERVE_ID_1, &5, 2_000));
+        assert_eq!(EthAdapter::reserved_balance(&5), 5_000);
+        assert_eq!(
+            EthAdapter::total_balance(&5),
+            EthAdapter::free_balance(&5) + EthAdapter::reserved_balance(&5)
+        );
+    });
+}
+
+const LOCK_ID_1: LockIdentifier = *b"lock_1  ";
+const LOCK_ID_2: LockIdentifier = *b"lock_2  ";
+
+#[test]
+fn test_lock_prevents_withdrawal() {
+    new_test_ext().execute_with(|| {
+        ERROR_COUNT.with(|v| v.replace(0));
+
+        ModuleBalances::set_lock(
+            Btc,
+            LOCK_ID_1,
+            &1,
+            600_000_000_000,
+            WithdrawReason::Transfer | WithdrawReason::Reserve,
+        );
+
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Btc, 3, 500_000_000_000),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_noop!(
+            ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 500_000_000_000),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_noop!(
+            ModuleBalances::ensure_can_withdraw(
+                Btc,
+                &1,
+                500_000_000_000,
+                WithdrawReason::Transfer.into(),
+                500_000_000_000
+            ),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(1),
+            Btc,
+            3,
+            400_000_000_000
+        ));
+        check_balance_and_debt(&1, 600_000_000_000, 0, Btc, line!());
+
+        // lock does not cover fees
+        assert_ok!(ModuleBalances::withdraw(
+            Btc,
+            &1,
+            100_000_000_000,
+            WithdrawReason::TransactionPayment.into(),
+            ExistenceRequirement::AllowDeath
+        ));
+        check_balance_and_debt(&1, 500_000_000_000, 0, Btc, line!());
+
+        ERROR_COUNT.with(|f| {
+            assert_eq!(*f.borrow(), 0);
+        });
+    });
+}
+
+#[test]
+fn test_extend_and_remove_lock() {
+    new_test_ext().execute_with(|| {
+        let reasons = WithdrawReasons::all();
+        ModuleBalances::set_lock(Btc, LOCK_ID_1, &1, 100, reasons);
+        ModuleBalances::extend_lock(Btc, LOCK_ID_1, &1, 50, reasons);
+        assert_eq!(ModuleBalances::frozen_balance(Btc, &1, reasons), 100);
+        ModuleBalances::extend_lock(Btc, LOCK_ID_1, &1, 300, reasons);
+        assert_eq!(ModuleBalances::frozen_balance(Btc, &1, reasons), 300);
+
+        ModuleBalances::set_lock(Btc, LOCK_ID_2, &1, 200, reasons);
+        assert_eq!(ModuleBalances::locks(1, Btc).len(), 2);
+        assert_eq!(ModuleBalances::frozen_balance(Btc, &1, reasons), 300);
+
+        ModuleBalances::remove_lock(Btc, LOCK_ID_1, &1);
+        assert_eq!(ModuleBalances::frozen_balance(Btc, &1, reasons), 200);
+        ModuleBalances::remove_lock(Btc, LOCK_ID_2, &1);
+        assert!(!Locks::<Test>::contains_key(1, Btc));
+    });
+}
+
+#[test]
+fn test_lockable_currency_adapter() {
+    new_test_ext().execute_with(|| {
+        let _ = EthAdapter::deposit_creating(&5, 10_000);
+
+        EthAdapter::set_lock(LOCK_ID_1, &5, 6_000, WithdrawReasons::all());
+        assert_noop!(
+            EthAdapter::transfer(&5, &6, 5_000, ExistenceRequirement::AllowDeath),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_ok!(EthAdapter::transfer(
+            &5,
+            &6,
+            4_000,
+            ExistenceRequirement::AllowDeath
+        ));
+
+        EthAdapter::remove_lock(LOCK_ID_1, &5);
+        assert_ok!(EthAdapter::transfer(
+            &5,
+            &6,
+            5_000,
+            ExistenceRequirement::AllowDeath
+        ));
+    });
+}
+
+fn events() -> Vec<TestEvent> {
+    let events = System::events()
+        .into_iter()
+        .map(|r| r.event)
+        .filter(|e| matches!(e, TestEvent::eq_balances(_)))
+        .collect::<Vec<_>>();
+    System::reset_events();
+    events
+}
+
+fn balances_event(event: RawEvent<u64, u64, u64>) -> TestEvent {
+    TestEvent::eq_balances(event)
+}
+
+#[test]
+fn test_deposit_and_withdraw_events() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(ModuleBalances::deposit_into_existing(Btc, &1, 100).map(|_| ()));
+        let _ = ModuleBalances::deposit_creating(Btc, &5, 200);
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::Deposit(1, Btc, 100)),
+                balances_event(RawEvent::Deposit(5, Btc, 200)),
+            ]
+        );
+
+        assert_ok!(ModuleBalances::withdraw(
+            Btc,
+            &5,
+            50,
+            WithdrawReasons::all(),
+            ExistenceRequirement::AllowDeath
+        )
+        .map(|_| ()));
+        assert_eq!(
+            events(),
+            vec![balances_event(RawEvent::Withdraw(5, Btc, 50))]
+        );
+
+        assert_ok!(ModuleBalances::deposit(Origin::root(), Btc, 5, 10));
+        assert_ok!(ModuleBalances::burn(Origin::root(), Btc, 5, 20));
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::Deposit(5, Btc, 10)),
+                balances_event(RawEvent::Issued(Btc, 10)),
+                balances_event(RawEvent::Withdraw(5, Btc, 20)),
+                balances_event(RawEvent::Burned(Btc, 20)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_transfer_and_slash_events() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(ModuleBalances::currency_transfer(
+            Btc,
+            &1,
+            &3,
+            100,
+            ExistenceRequirement::AllowDeath
+        ));
+        assert_eq!(
+            events(),
+            vec![balances_event(RawEvent::Transfer(1, 3, Btc, 100))]
+        );
+
+        let _ = ModuleBalances::slash(Btc, &3, 40);
+        assert_eq!(
+            events(),
+            vec![balances_event(RawEvent::Slashed(3, Btc, 40))]
+        );
+
+        // unsettled imbalances restore the issuance and leave no events
+        let _ = <ModuleBalances as EqCurrency<u64, u64>>::burn(Btc, 10);
+        let _ = ModuleBalances::issue(Btc, 20);
+        assert!(events().is_empty());
+        check_balances_aggregates(Btc, 2999_999_999_960, 0, line!());
+    });
+}
+
+#[test]
+fn test_balance_set_events() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let _ = ModuleBalances::make_free_balance_be(Btc, &1, 500);
+        assert_ok!(ModuleBalances::set_balance_with_agg_unsafe(
+            &2,
+            &Btc,
+            SignedBalance::Positive(300)
+        ));
+        ModuleBalances::add_balance_unsafe(&2, &Btc, &SignedBalance::Positive(100));
+        assert_ok!(ModuleBalances::remove_balance_with_agg_unsafe(&2, &Btc));
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::BalanceSet(1, Btc, SignedBalance::Positive(500))),
+                balances_event(RawEvent::BalanceSet(2, Btc, SignedBalance::Positive(300))),
+                balances_event(RawEvent::BalanceSet(2, Btc, SignedBalance::Positive(400))),
+                balances_event(RawEvent::BalanceSet(2, Btc, SignedBalance::zero())),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_debt_events() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(10),
+            Usd,
+            20,
+            15_000_000_000
+        ));
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::DebtCreated(10, Usd, 5_000_000_000)),
+                balances_event(RawEvent::Transfer(10, 20, Usd, 15_000_000_000)),
+            ]
+        );
+
+        assert_ok!(ModuleBalances::transfer(
+            Origin::signed(30),
+            Usd,
+            10,
+            6_000_000_000
+        ));
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::DebtRepaid(10, Usd, 5_000_000_000)),
+                balances_event(RawEvent::Transfer(30, 10, Usd, 6_000_000_000)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_account_reaped_event() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        let _ = EthAdapter::deposit_creating(&5, 1_500);
+        events();
+
+        assert_ok!(ModuleBalances::currency_transfer(
+            Eth,
+            &5,
+            &6,
+            1_000,
+            ExistenceRequirement::AllowDeath
+        ));
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::AccountReaped(5, Eth, 500)),
+                balances_event(RawEvent::Transfer(5, 6, Eth, 1_000)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_balances_of() {
+    new_test_ext().execute_with(|| {
+        let _ = ModuleBalances::deposit_creating(Eth, &1, 5_000);
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Usd, 10, 100));
+
+        let mut balances = ModuleBalances::balances_of(&1);
+        balances.sort_by_key(|(currency, _)| currency.value());
+        assert_eq!(
+            balances,
+            vec![
+                (Usd, SignedBalance::Negative(100)),
+                (Eth, SignedBalance::Positive(5_000)),
+                (Btc, SignedBalance::Positive(1000_000_000_000)),
+            ]
+        );
+        assert!(ModuleBalances::balances_of(&5).is_empty());
+    });
+}
+
+#[test]
+fn test_account_data_keeps_currencies_ordered() {
+    let mut account = AccountData::<u64>::default();
+    account.set_balance(&Btc, SignedBalance::Positive(10));
+    account.set_balance(&Usd, SignedBalance::Negative(20));
+    account.set_balance(&Eth, SignedBalance::Positive(30));
+    account.set_balance(&Btc, SignedBalance::Positive(40));
+    assert_eq!(
+        account.balances(),
+        &[
+            (Usd, SignedBalance::Negative(20)),
+            (Eth, SignedBalance::Positive(30)),
+            (Btc, SignedBalance::Positive(40)),
+        ][..]
+    );
+
+    account.set_balance(&Eth, SignedBalance::zero());
+    assert_eq!(account.balance(&Eth), SignedBalance::zero());
+    assert_eq!(account.balances().len(), 2);
+}
+
+#[test]
+fn test_migrate_to_single_entry() {
+    use frame_support::storage::migration::put_storage_value;
+    use frame_support::{Blake2_128Concat, StorageHasher};
+
+    new_test_ext().execute_with(|| {
+        let old_balances = vec![
+            (5u64, Btc, SignedBalance::Positive(100u64)),
+            (5, Usd, SignedBalance::Negative(50)),
+            (6, Eth, SignedBalance::Positive(2_000)),
+            (7, Eth, SignedBalance::zero()),
+        ];
+        for (who, currency, balance) in old_balances.iter() {
+            let mut key = Blake2_128Concat::hash(&who.encode());
+            key.extend(Blake2_128Concat::hash(&currency.encode()));
+            put_storage_value(b"EqBalances", b"Account", &key, balance.clone());
+        }
+        StorageVersion::put(Releases::V1DoubleMap);
+
+        ModuleBalances::migrate();
+
+        assert_eq!(StorageVersion::get(), Releases::V2SingleEntry);
+        assert_eq!(
+            ModuleBalances::balances_of(&5),
+            vec![
+                (Usd, SignedBalance::Negative(50)),
+                (Btc, SignedBalance::Positive(100)),
+            ]
+        );
+        assert_eq!(
+            ModuleBalances::balances_of(&6),
+            vec![(Eth, SignedBalance::Positive(2_000))]
+        );
+        assert_eq!(
+            ModuleBalances::balances_of(&1),
+            vec![(Btc, SignedBalance::Positive(1000_000_000_000))]
+        );
+        assert!(!Accounts::<Test>::contains_key(&7));
+
+        // migrated accounts are referenced by the module
+        assert_eq!(system::Module::<Test>::refs(&5), 1);
+        assert_eq!(system::Module::<Test>::refs(&6), 1);
+        assert_eq!(system::Module::<Test>::refs(&7), 0);
+        assert_eq!(system::Module::<Test>::refs(&1), 1);
+        ModuleBalances::balances_aggregates_fix();
+        assert_ok!(ModuleBalances::transfer(Origin::signed(6), Eth, 1, 2_000));
+        assert_eq!(system::Module::<Test>::refs(&6), 0);
+
+        assert!(
+            StorageIterator::<SignedBalance<u64>>::new(b"EqBalances", b"Account")
+                .next()
+                .is_none()
+        );
+
+        // runs only once
+        assert_eq!(
+            ModuleBalances::migrate(),
+            <Test as system::Trait>::DbWeight::get().reads(1)
+        );
+    });
+}
+
+#[test]
+fn test_balance_checkers_return_first_error() {
+    struct Allow;
+    struct Frozen;
+    struct Insufficient;
+
+    impl BalanceChecker<u64, u64> for Allow {
+        fn can_change_balance(
+            _: &u64,
+            _: &currency::Currency,
+            _: &SignedBalance<u64>,
+        ) -> DispatchResult {
+            Ok(())
+        }
+    }
+    impl BalanceChecker<u64, u64> for Frozen {
+        fn can_change_balance(
+            _: &u64,
+            _: &currency::Currency,
+            _: &SignedBalance<u64>,
+        ) -> DispatchResult {
+            Err(Error::<Test>::LiquidityRestrictions.into())
+        }
+    }
+    impl BalanceChecker<u64, u64> for Insufficient {
+        fn can_change_balance(
+            _: &u64,
+            _: &currency::Currency,
+            _: &SignedBalance<u64>,
+        ) -> DispatchResult {
+            Err(Error::<Test>::InsufficientBalance.into())
+        }
+    }
+
+    let change = SignedBalance::Negative(10);
+    assert_ok!(<(Allow, Allow)>::can_change_balance(
+        &1,
+        &currency::Currency::Usd,
+        &change
+    ));
+    assert_eq!(
+        <(Allow, Frozen, Insufficient)>::can_change_balance(&1, &currency::Currency::Usd, &change),
+        Err(Error::<Test>::LiquidityRestrictions.into())
+    );
+    assert_eq!(
+        <(Insufficient, Frozen)>::can_change_balance(&1, &currency::Currency::Usd, &change),
+        Err(Error::<Test>::InsufficientBalance.into())
+    );
+}
+
+type CreditLimit = CreditLimitChecker<Test, PriceGetterMock>;
+
+#[test]
+fn test_credit_limit_requires_collateral_ratio() {
+    new_test_ext().execute_with(|| {
+        // account 10 has 10_000 USD worth of USD collateral
+        assert_noop!(
+            CreditLimit::can_change_balance(&10, &currency::Currency::Eth, &Negative(1)),
+            Error::<Test>::DebtNotAllowed
+        );
+        // spending own funds doesn't need a ratio
+        assert_ok!(CreditLimit::can_change_balance(
+            &10,
+            &currency::Currency::Usd,
+            &Negative(10_000_000_000)
+        ));
+        assert_ok!(CreditLimit::can_change_balance(
+            &10,
+            &currency::Currency::Eth,
+            &Positive(1)
+        ));
+
+        assert_noop!(
+            ModuleBalances::set_collateral_ratio(
+                Origin::root(),
+                currency::Currency::Eth,
+                Some(FixedU128::saturating_from_rational(1, 2))
+            ),
+            Error::<Test>::InvalidCollateralRatio
+        );
+        assert_ok!(ModuleBalances::set_collateral_ratio(
+            Origin::root(),
+            currency::Currency::Eth,
+            Some(FixedU128::saturating_from_integer(2))
+        ));
+        assert_eq!(
+            ModuleBalances::collateral_ratios(currency::Currency::Eth),
+            Some(FixedU128::saturating_from_integer(2))
+        );
+        assert_ok!(CreditLimit::can_change_balance(
+            &10,
+            &currency::Currency::Eth,
+            &Negative(1)
+        ));
+
+        assert_ok!(ModuleBalances::set_collateral_ratio(
+            Origin::root(),
+            currency::Currency::Eth,
+            None
+        ));
+        assert_eq!(
+            ModuleBalances::collateral_ratios(currency::Currency::Eth),
+            None
+        );
+    });
+}
+
+#[test]
+fn test_credit_limit_multi_currency_portfolio() {
+    new_test_ext().execute_with(|| {
+        use currency::Currency::{Btc, Eq, Eth, Usd};
+        let who = 5;
+        ModuleBalances::make_free_balance_be(Btc, &who, 1); // 10_000
+        ModuleBalances::make_free_balance_be(Eq, &who, 40_000); // 10_000
+        ModuleBalances::make_free_balance_be(Usd, &who, 5_000); // 5_000
+
+        assert_ok!(ModuleBalances::set_collateral_ratio(
+            Origin::root(),
+            Eth,
+            Some(FixedU128::saturating_from_integer(2))
+        ));
+        assert_ok!(ModuleBalances::set_collateral_ratio(
+            Origin::root(),
+            Usd,
+            Some(FixedU128::saturating_from_rational(5, 4))
+        ));
+
+        // 25_000 of collateral covers 50 ETH at ratio 2
+        assert_ok!(CreditLimit::can_change_balance(&who, &Eth, &Negative(50)));
+        assert_noop!(
+            CreditLimit::can_change_balance(&who, &Eth, &Negative(51)),
+            Error::<Test>::CreditLimitExceeded
+        );
+
+        assert_ok!(ModuleBalances::set_balance_with_agg_unsafe(
+            &who,
+            &Eth,
+            Negative(40)
+        ));
+        // 20_000 of required collateral for 40 ETH, 5_000 left
+        assert_ok!(CreditLimit::can_change_balance(&who, &Eth, &Negative(10)));
+        assert_noop!(
+            CreditLimit::can_change_balance(&who, &Eth, &Negative(11)),
+            Error::<Test>::CreditLimitExceeded
+        );
+
+        // spending USD reduces collateral first, then borrows USD at ratio 5/4
+        assert_ok!(CreditLimit::can_change_balance(
+            &who,
+            &Usd,
+            &Negative(5_000)
+        ));
+        assert_noop!(
+            CreditLimit::can_change_balance(&who, &Usd, &Negative(5_001)),
+            Error::<Test>::CreditLimitExceeded
+        );
+
+        // withdrawing collateral of a currency without a ratio is checked as well
+        assert_noop!(
+            CreditLimit::can_change_balance(&who, &Eq, &Negative(20_001)),
+            Error::<Test>::CreditLimitExceeded
+        );
+        assert_ok!(CreditLimit::can_change_balance(
+            &who,
+            &Eq,
+            &Negative(20_000)
+        ));
+    });
+}
+
+#[test]
+fn test_credit_limit_fails_without_price() {
+    new_test_ext().execute_with(|| {
+        let who = 5;
+        ModuleBalances::make_free_balance_be(currency::Currency::Eos, &who, 1_000);
+        assert_ok!(ModuleBalances::set_collateral_ratio(
+            Origin::root(),
+            currency::Currency::Usd,
+            Some(FixedU128::saturating_from_integer(1))
+        ));
+
+        assert_noop!(
+            CreditLimit::can_change_balance(&who, &currency::Currency::Usd, &Negative(1)),
+            DispatchError::Other("no price")
+        );
+    });
+}
+
+#[test]
+fn test_force_transfer_bypasses_checks_and_keeps_aggregates() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        ModuleBalances::set_lock(
+            Usd,
+            *b"testlock",
+            &10,
+            10_000_000_000,
+            WithdrawReasons::all(),
+        );
+
+        assert_noop!(
+            ModuleBalances::force_transfer(Origin::signed(10), Usd, 10, 20, 1),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(ModuleBalances::force_transfer(
+            Origin::root(),
+            Usd,
+            10,
+            20,
+            15_000_000_000
+        ));
+
+        assert_eq!(
+            ModuleBalances::get_balance(&10, &Usd),
+            Negative(5_000_000_000)
+        );
+        assert_eq!(
+            ModuleBalances::get_balance(&20, &Usd),
+            Positive(35_000_000_000)
+        );
+        let aggregate = ModuleBalances::balances_aggregates_get(&Usd);
+        assert_eq!(aggregate.total_issuance, 65_000_000_000);
+        assert_eq!(aggregate.total_debt, 5_000_000_000);
+        assert!(events().contains(&balances_event(RawEvent::Transfer(
+            10,
+            20,
+            Usd,
+            15_000_000_000
+        ))));
+    });
+}
+
+#[test]
+fn test_force_set_balance_keeps_aggregates() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_noop!(
+            ModuleBalances::force_set_balance(Origin::signed(10), Usd, 10, Positive(1)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(ModuleBalances::force_set_balance(
+            Origin::root(),
+            Usd,
+            10,
+            Negative(1_000)
+        ));
+        assert_ok!(ModuleBalances::force_set_balance(
+            Origin::root(),
+            Usd,
+            5,
+            Positive(2_000)
+        ));
+
+        assert_eq!(ModuleBalances::get_balance(&10, &Usd), Negative(1_000));
+        assert_eq!(ModuleBalances::get_balance(&5, &Usd), Positive(2_000));
+        let aggregate = ModuleBalances::balances_aggregates_get(&Usd);
+        assert_eq!(aggregate.total_issuance, 50_000_002_000);
+        assert_eq!(aggregate.total_debt, 1_000);
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::DebtCreated(10, Usd, 1_000)),
+                balances_event(RawEvent::BalanceSet(10, Usd, Negative(1_000))),
+                balances_event(RawEvent::BalanceSet(5, Usd, Positive(2_000))),
+            ]
+        );
+
+        assert_ok!(ModuleBalances::force_set_balance(
+            Origin::root(),
+            Usd,
+            10,
+            Positive(0)
+        ));
+        assert_eq!(ModuleBalances::balances_of(&10), vec![]);
+        assert_eq!(ModuleBalances::balances_aggregates_get(&Usd).total_debt, 0);
+    });
+}
+
+#[test]
+fn test_force_calls_reject_unknown_currency() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            ModuleBalances::force_transfer(Origin::root(), Unknown, 10, 20, 1),
+            Error::<Test>::UnknownCurrency
+        );
+        assert_noop!(
+            ModuleBalances::force_set_balance(Origin::root(), Unknown, 10, Positive(1)),
+            Error::<Test>::UnknownCurrency
+        );
+    });
+}
+
+#[test]
+fn test_force_calls_respect_existential_deposit() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(ModuleBalances::force_set_balance(
+            Origin::root(),
+            Eth,
+            5,
+            Positive(1_500)
+        ));
+        assert_noop!(
+            ModuleBalances::force_set_balance(Origin::root(), Eth, 6, Positive(999)),
+            Error::<Test>::ExistentialDeposit
+        );
+        assert_noop!(
+            ModuleBalances::force_transfer(Origin::root(), Eth, 5, 6, 999),
+            Error::<Test>::ExistentialDeposit
+        );
+
+        // 500 left on the source is below existential deposit and is reaped
+        assert_ok!(ModuleBalances::force_transfer(
+            Origin::root(),
+            Eth,
+            5,
+            6,
+            1_000
+        ));
+        assert_eq!(ModuleBalances::balances_of(&5), vec![]);
+        assert_eq!(ModuleBalances::get_balance(&6, &Eth), Positive(1_000));
+        assert_eq!(
+            ModuleBalances::get_balance(&TREASURY_ACCOUNT_ID, &Eth),
+            Positive(500)
+        );
+        assert_eq!(
+            ModuleBalances::balances_aggregates_get(&Eth).total_issuance,
+            1_500
+        );
+    });
+}
+
+#[test]
+fn test_transfer_many_moves_all_legs() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        ModuleBalances::make_free_balance_be(Eth, &1, 10_000);
+        events();
+
+        assert_ok!(ModuleBalances::transfer_many(
+            Origin::signed(1),
+            vec![
+                (Btc, 2, 100),
+                (Eth, 5, 3_000),
+                (Btc, 5, 50),
+                (Eth, 2, 2_000)
+            ]
+        ));
+
+        assert_eq!(
+            ModuleBalances::get_balance(&1, &Btc),
+            Positive(999_999_999_850)
+        );
+        assert_eq!(ModuleBalances::get_balance(&1, &Eth), Positive(5_000));
+        assert_eq!(
+            ModuleBalances::get_balance(&2, &Btc),
+            Positive(2_000_000_000_100)
+        );
+        assert_eq!(ModuleBalances::get_balance(&2, &Eth), Positive(2_000));
+        assert_eq!(ModuleBalances::get_balance(&5, &Btc), Positive(50));
+        assert_eq!(ModuleBalances::get_balance(&5, &Eth), Positive(3_000));
+        assert_eq!(
+            ModuleBalances::balances_aggregates_get(&Btc).total_issuance,
+            3_000_000_000_000
+        );
+        assert_eq!(
+            ModuleBalances::balances_aggregates_get(&Eth).total_issuance,
+            10_000
+        );
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::Transfer(1, 2, Btc, 100)),
+                balances_event(RawEvent::Transfer(1, 5, Eth, 3_000)),
+                balances_event(RawEvent::Transfer(1, 5, Btc, 50)),
+                balances_event(RawEvent::Transfer(1, 2, Eth, 2_000)),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_transfer_many_is_all_or_nothing() {
+    new_test_ext().execute_with(|| {
+        ModuleBalances::make_free_balance_be(Eth, &1, 10_000);
+        ModuleBalances::set_lock(Eth, *b"testlock", &1, 6_000, WithdrawReasons::all());
+
+        // new account gets less than existential deposit
+        assert_noop!(
+            ModuleBalances::transfer_many(Origin::signed(1), vec![(Btc, 2, 100), (Eth, 5, 10)]),
+            Error::<Test>::ExistentialDeposit
+        );
+        // legs are locked together, each of them alone is below the lock
+        assert_noop!(
+            ModuleBalances::transfer_many(
+                Origin::signed(1),
+                vec![(Eth, 2, 3_000), (Btc, 2, 100), (Eth, 5, 3_000)]
+            ),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_noop!(
+            ModuleBalances::transfer_many(
+                Origin::signed(1),
+                vec![(Btc, 2, 100), (Btc, 5, u64::max_value())]
+            ),
+            Error::<Test>::Overflow
+        );
+    });
+}
+
+#[test]
+fn test_transfer_many_checks_legs_after_previous_ones() {
+    new_test_ext().execute_with(|| {
+        BalanceCheckerMock::require_collateral(1);
+
+        // Btc sent by the first leg no longer covers Eth borrowed by the second one
+        assert_noop!(
+            ModuleBalances::transfer_many(
+                Origin::signed(1),
+                vec![(Btc, 2, 1000_000_000_000), (Eth, 5, 5_000)]
+            ),
+            Error::<Test>::LiquidityRestrictions
+        );
+        assert_noop!(
+            ModuleBalances::transfer_many(
+                Origin::signed(1),
+                vec![(Eth, 5, 5_000), (Btc, 2, 1000_000_000_000)]
+            ),
+            Error::<Test>::LiquidityRestrictions
+        );
+
+        assert_ok!(ModuleBalances::transfer_many(
+            Origin::signed(1),
+            vec![(Btc, 2, 500_000_000_000), (Eth, 5, 5_000)]
+        ));
+        assert_eq!(ModuleBalances::get_balance(&1, &Eth), Negative(5_000));
+        assert_eq!(
+            ModuleBalances::get_balance(&1, &Btc),
+            Positive(500_000_000_000)
+        );
+    });
+}
+
+#[test]
+fn test_aggregates_underflow_is_an_error() {
+    new_test_ext().execute_with(|| {
+        // drift left after an unsafe write
+        ModuleBalances::set_balance_unsafe(&5, &Usd, Negative(100));
+
+        assert_noop!(
+            ModuleBalances::deposit_into_existing(Usd, &5, 50).map(|_| ()),
+            Error::<Test>::AggregatesUnderflow
+        );
+        assert_eq!(
+            ModuleBalances::audit_aggregates(),
+            vec![(
+                Usd,
+                BalancesAggregate {
+                    total_issuance: 60_000_000_000,
+                    total_debt: 0
+                },
+                BalancesAggregate {
+                    total_issuance: 60_000_000_000,
+                    total_debt: 100
+                }
+            )]
+        );
+    });
+}
+
+#[test]
+fn test_repair_aggregates_in_chunks() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        ModuleBalances::set_balance_unsafe(&5, &Usd, Negative(70));
+        // module entries are repaired even without a system account
+        system::Account::<Test>::remove(&5);
+        assert_ok!(ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 1_000));
+        <BalancesAggregates<Test>>::remove(Btc);
+        events();
+
+        assert_noop!(
+            ModuleBalances::repair_aggregates(Origin::signed(1), 2),
+            DispatchError::BadOrigin
+        );
+        // six accounts and one reserve are processed in four chunks
+        assert_ok!(ModuleBalances::repair_aggregates(Origin::root(), 2));
+        assert!(ModuleBalances::aggregates_repair().is_some());
+        // changes of processed and pending accounts during the repair are not drift
+        for who in [1, 2, 10, 20, 30].iter() {
+            assert_ok!(ModuleBalances::deposit_into_existing(Usd, who, 1).map(|_| ()));
+        }
+        events();
+        assert_ok!(ModuleBalances::repair_aggregates(Origin::root(), 2));
+        assert_ok!(ModuleBalances::repair_aggregates(Origin::root(), 2));
+        assert!(events().is_empty());
+        assert_ok!(ModuleBalances::repair_aggregates(Origin::root(), 2));
+
+        assert!(ModuleBalances::aggregates_repair().is_none());
+        assert!(ModuleBalances::audit_aggregates().is_empty());
+        let events = events();
+        assert_eq!(events.len(), 2);
+        assert!(
+            events.contains(&balances_event(RawEvent::AggregatesRepaired(
+                Usd,
+                BalancesAggregate {
+                    total_issuance: 60_000_000_005,
+                    total_debt: 0
+                },
+                BalancesAggregate {
+                    total_issuance: 60_000_000_005,
+                    total_debt: 70
+                }
+            )))
+        );
+        assert!(
+            events.contains(&balances_event(RawEvent::AggregatesRepaired(
+                Btc,
+                BalancesAggregate::default(),
+                BalancesAggregate {
+                    total_issuance: 3_000_000_000_000,
+                    total_debt: 0
+                }
+            )))
+        );
+    });
+}
+
+#[test]
+fn test_freeze_currency() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_noop!(
+            ModuleBalances::freeze(Origin::signed(1), 1, Some(Btc)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(ModuleBalances::freeze(Origin::root(), 1, Some(Btc)));
+        assert_eq!(
+            events(),
+            vec![balances_event(RawEvent::Frozen(1, Some(Btc)))]
+        );
+        assert!(ModuleBalances::is_frozen(&1, &Btc));
+        assert!(!ModuleBalances::is_frozen(&1, &Usd));
+
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100),
+            Error::<Test>::AccountFrozen
+        );
+        assert_noop!(
+            ModuleBalances::transfer_many(Origin::signed(1), vec![(Btc, 2, 100)]),
+            Error::<Test>::AccountFrozen
+        );
+        assert_noop!(
+            ModuleBalances::withdraw(
+                Btc,
+                &1,
+                100,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath
+            )
+            .map(|_| ()),
+            Error::<Test>::AccountFrozen
+        );
+        assert_noop!(
+            ModuleBalances::ensure_can_withdraw(
+                Btc,
+                &1,
+                100,
+                WithdrawReasons::all(),
+                999_999_999_900
+            ),
+            Error::<Test>::AccountFrozen
+        );
+        assert!(!ModuleBalances::can_reserve(Btc, &1, 100));
+        assert_noop!(
+            ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 100),
+            Error::<Test>::AccountFrozen
+        );
+
+        // frozen account still receives funds and moves other currencies
+        assert_ok!(ModuleBalances::transfer(Origin::signed(2), Btc, 1, 100));
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Usd, 10, 100));
+        check_balance_and_debt(&1, 1_000_000_000_100, 0, Btc, line!());
+
+        assert_ok!(ModuleBalances::thaw(Origin::root(), 1, Some(Btc)));
+        assert!(!ModuleBalances::is_frozen(&1, &Btc));
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+    });
+}
+
+#[test]
+fn test_freeze_all_currencies() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        assert_ok!(ModuleBalances::freeze(Origin::root(), 10, None));
+        assert_ok!(ModuleBalances::freeze(Origin::root(), 10, Some(Usd)));
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(10), Btc, 2, 100),
+            Error::<Test>::AccountFrozen
+        );
+
+        assert_ok!(ModuleBalances::thaw(Origin::root(), 10, None));
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(10), Usd, 20, 100),
+            Error::<Test>::AccountFrozen
+        );
+        assert_eq!(
+            events(),
+            vec![
+                balances_event(RawEvent::Frozen(10, None)),
+                balances_event(RawEvent::Frozen(10, Some(Usd))),
+                balances_event(RawEvent::Thawed(10, None)),
+            ]
+        );
+    });
+}
+
+fn registry_asset(existential_deposit: u64, status: AssetStatus) -> AssetData<u64> {
+    AssetData {
+        symbol: b"TST".to_vec(),
+        decimals: 9,
+        existential_deposit,
+        status,
+    }
+}
+
+#[test]
+fn test_registry_existential_deposit() {
+    new_test_ext().execute_with(|| {
+        AssetGetterMock::set_asset(Btc, registry_asset(500, AssetStatus::Active));
+        AssetGetterMock::set_asset(Eth, registry_asset(500, AssetStatus::Active));
+
+        assert_eq!(ModuleBalances::currency_minimum_balance(Btc), 500);
+        assert_eq!(ModuleBalances::currency_minimum_balance(Eth), 500);
+        assert_eq!(ModuleBalances::currency_minimum_balance(Eos), 1);
+
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Btc, 3, 499),
+            Error::<Test>::ExistentialDeposit
+        );
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 3, 500));
+    });
+}
+
+#[test]
+fn test_inactive_currency() {
+    new_test_ext().execute_with(|| {
+        AssetGetterMock::set_asset(Btc, registry_asset(1, AssetStatus::Frozen));
+
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100),
+            Error::<Test>::CurrencyNotActive
+        );
+        assert_noop!(
+            ModuleBalances::transfer_many(Origin::signed(10), vec![(Usd, 20, 1), (Btc, 2, 1)]),
+            Error::<Test>::CurrencyNotActive
+        );
+        assert_noop!(
+            ModuleBalances::deposit(Origin::root(), Btc, 3, 100),
+            Error::<Test>::CurrencyNotActive
+        );
+        assert_eq!(ModuleBalances::deposit_creating(Btc, &3, 100).peek(), 0);
+        assert_noop!(
+            ModuleBalances::withdraw(
+                Btc,
+                &1,
+                100,
+                WithdrawReasons::all(),
+                ExistenceRequirement::AllowDeath
+            )
+            .map(|_| ()),
+            Error::<Test>::CurrencyNotActive
+        );
+
+        AssetGetterMock::set_asset(Btc, registry_asset(1, AssetStatus::Delisted));
+        assert_noop!(
+            ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100),
+            Error::<Test>::CurrencyNotActive
+        );
+
+        AssetGetterMock::set_asset(Btc, registry_asset(1, AssetStatus::Active));
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+    });
+}
+
+#[test]
+fn test_aggregates_fix_iterates_registry() {
+    let other = currency::Currency::Other(100);
+    AssetGetterMock::set_asset(other, registry_asset(1, AssetStatus::Active));
+    genesis_ext(vec![(1, other, Positive(700))]).execute_with(|| {
+        check_balances_aggregates(other, 700, 0, line!());
+
+        <BalancesAggregates<Test>>::insert(
+            currency::Currency::Other(101),
+            BalancesAggregate {
+                total_issuance: 5,
+                total_debt: 0,
+            },
+        );
+        AssetGetterMock::set_asset(
+            currency::Currency::Other(101),
+            registry_asset(1, AssetStatus::Delisted),
+        );
+        ModuleBalances::balances_aggregates_fix();
+        check_balances_aggregates(other, 700, 0, line!());
+        check_balances_aggregates(currency::Currency::Other(101), 0, 0, line!());
+    });
+}
+
+#[test]
+fn test_balance_checkpoints() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        ModuleBalances::on_initialize(10);
+        assert_eq!(ModuleBalances::checkpoints(), vec![10]);
+        assert_eq!(
+            events(),
+            vec![balances_event(RawEvent::CheckpointCreated(10))]
+        );
+
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+        assert_eq!(ModuleBalances::balance_at(&1, &Btc, 9), None);
+        assert_eq!(
+            ModuleBalances::balance_at(&1, &Btc, 15),
+            Some(SignedBalance::Positive(1_000_000_000_000))
+        );
+
+        System::set_block_number(15);
+        assert_noop!(
+            ModuleBalances::checkpoint(Origin::signed(1)),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(ModuleBalances::checkpoint(Origin::root()));
+        assert_noop!(
+            ModuleBalances::checkpoint(Origin::root()),
+            Error::<Test>::CheckpointExists
+        );
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+
+        assert_eq!(
+            ModuleBalances::balance_at(&1, &Btc, 14),
+            Some(SignedBalance::Positive(1_000_000_000_000))
+        );
+        assert_eq!(
+            ModuleBalances::balance_at(&1, &Btc, 15),
+            Some(SignedBalance::Positive(999_999_999_800))
+        );
+        assert_eq!(
+            ModuleBalances::balance_at(&1, &Btc, 100),
+            Some(SignedBalance::Positive(999_999_999_800))
+        );
+        // unchanged balances are read from the account
+        assert_eq!(
+            ModuleBalances::balance_at(&10, &Usd, 10),
+            Some(SignedBalance::Positive(10_000_000_000))
+        );
+    });
+}
+
+#[test]
+fn test_balance_checkpoints_pruning() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        ModuleBalances::on_initialize(10);
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+
+        for block in 11..=40 {
+            System::set_block_number(block);
+            ModuleBalances::on_initialize(block);
+        }
+        assert_eq!(ModuleBalances::checkpoints(), vec![10, 20, 30, 40]);
+
+        System::set_block_number(41);
+        ModuleBalances::on_initialize(41);
+        assert_eq!(ModuleBalances::checkpoints(), vec![20, 30, 40]);
+        assert_eq!(ModuleBalances::balance_at(&1, &Btc, 15), None);
+
+        // balances of the removed checkpoint are pruned one entry per block
+        assert!(ModuleBalances::checkpoints_pruning().is_some());
+        assert_eq!(<BalanceCheckpoints<Test>>::iter().count(), 1);
+        System::set_block_number(42);
+        ModuleBalances::on_initialize(42);
+        assert_eq!(<BalanceCheckpoints<Test>>::iter().count(), 0);
+        System::set_block_number(43);
+        ModuleBalances::on_initialize(43);
+        assert!(ModuleBalances::checkpoints_pruning().is_none());
+
+        assert_eq!(
+            ModuleBalances::balance_at(&1, &Btc, 25),
+            Some(SignedBalance::Positive(999_999_999_900))
+        );
+
+        assert_ok!(ModuleBalances::transfer(Origin::signed(1), Btc, 2, 100));
+        assert_eq!(
+            ModuleBalances::balance_checkpoints(&1, &Btc),
+            vec![(40, SignedBalance::Positive(999_999_999_900))]
+        );
+    });
+}
+
+/// Escrow written only against orml traits: locks `deposit` of the buyer, pays `price`
+/// to the seller and charges `fee` through a signed balance update
+fn orml_escrow<C>(
+    currency: C::CurrencyId,
+    buyer: &u64,
+    seller: &u64,
+    price: C::Balance,
+    deposit: C::Balance,
+    fee: C::Amount,
+) -> DispatchResult
+where
+    C: orml_traits::MultiCurrencyExtended<u64> + orml_traits::MultiReservableCurrency<u64>,
+{
+    C::ensure_can_withdraw(currency, buyer, price + deposit)?;
+    C::reserve(currency, buyer, deposit)?;
+    C::transfer(currency, buyer, seller, price)?;
+    C::update_balance(currency, buyer, -fee)
+}
+
+#[test]
+fn test_orml_multi_currency() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(orml_escrow::<ModuleBalances>(Btc, &1, &5, 1_000, 300, 20));
+        check_balance_and_debt(&1, 999_999_998_680, 0, Btc, line!());
+        check_balance_and_debt(&5, 1_000, 0, Btc, line!());
+        assert_eq!(
+            <ModuleBalances as orml_traits::MultiCurrency<u64>>::total_balance(Btc, &1),
+            999_999_998_980
+        );
+        assert_eq!(
+            <ModuleBalances as orml_traits::MultiReservableCurrency<u64>>::reserved_balance(
+                Btc, &1
+            ),
+            300
+        );
+        check_balances_aggregates(Btc, 2_999_999_999_980, 0, line!());
+
+        assert_ok!(<ModuleBalances as orml_traits::MultiCurrencyExtended<
+            u64,
+        >>::update_balance(Usd, &5, 50));
+        check_balance_and_debt(&5, 50, 0, Usd, line!());
+        check_balances_aggregates(Usd, 60_000_000_050, 0, line!());
+        assert_noop!(
+            <ModuleBalances as orml_traits::MultiCurrencyExtended<u64>>::update_balance(
+                Usd,
+                &5,
+                i64::min_value()
+            ),
+            Error::<Test>::AmountIntoBalanceFailed
+        );
+    });
+}
+
+#[test]
+fn test_orml_total_balance_with_named_reserve() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(<ModuleBalances as orml_traits::MultiReservableCurrency<
+            u64,
+        >>::reserve(Btc, &1, 300));
+        assert_ok!(ModuleBalances::reserve_named(Btc, &RESERVE_ID_1, &1, 200));
+
+        assert_eq!(
+            <ModuleBalances as orml_traits::MultiReservableCurrency<u64>>::reserved_balance(
+                Btc, &1
+            ),
+            300
+        );
+        assert_eq!(
+            <ModuleBalances as orml_traits::MultiCurrency<u64>>::total_balance(Btc, &1),
+            <ModuleBalances as orml_traits::MultiCurrency<u64>>::free_balance(Btc, &1) + 300
+        );
+    });
+}
+
+#[test]
+fn test_orml_deposit_creates_account() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            <ModuleBalances as orml_traits::MultiCurrency<u64>>::deposit(Eth, &7, 999),
+            Error::<Test>::ExistentialDeposit
+        );
+        assert_ok!(<ModuleBalances as orml_traits::MultiCurrency<u64>>::deposit(Eth, &7, 1_000));
+        assert_ok!(<ModuleBalances as orml_traits::MultiCurrency<u64>>::deposit(Eth, &7, 1));
+        check_balance_and_debt(&7, 1_001, 0, Eth, line!());
+        check_balances_aggregates(Eth, 1_001, 0, line!());
+    });
+}
+
+#[test]
+fn test_orml_slash_reaches_reserve() {
+    new_test_ext().execute_with(|| {
+        let _ = ModuleBalances::deposit_creating(Btc, &5, 1_000);
+        assert_ok!(<ModuleBalances as orml_traits::MultiReservableCurrency<
+            u64,
+        >>::reserve(Btc, &5, 400));
+
+        let remaining = <ModuleBalances as orml_traits::MultiCurrency<u64>>::slash(Btc, &5, 800);
+        assert_eq!(remaining, 0);
+        check_balance_and_debt(&5, 0, 0, Btc, line!());
+        assert_eq!(ModuleBalances::reserved_balance(Btc, &5), 200);
+        check_balances_aggregates(Btc, 3_000_000_000_200, 0, line!());
+    });
+}
+
+fn genesis_ext(
+    balances: Vec<(u64, currency::Currency, SignedBalance<u64>)>,
+) -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default()
+        .build_storage::<Test>()
+        .unwrap();
+    GenesisConfig::<Test> { balances }
+        .assimilate_storage(&mut t)
+        .unwrap();
+    t.into()
+}
+
+#[test]
+fn test_genesis_with_debt() {
+    genesis_ext(vec![
+        (1, Btc, Positive(500)),
+        (2, Btc, Negative(200)),
+        (2, Usd, Positive(100)),
+    ])
+    .execute_with(|| {
+        check_balance_and_debt(&2, 0, 200, Btc, line!());
+        check_balances_aggregates(Btc, 500, 200, line!());
+        check_balances_aggregates(Usd, 100, 0, line!());
+        assert!(ModuleBalances::audit_aggregates().is_empty());
+    });
+}
+
+#[test]
+fn test_genesis_aggregates_of_unlisted_currency() {
+    let other = currency::Currency::Other(100);
+    genesis_ext(vec![(1, other, Positive(500)), (2, other, Negative(200))]).execute_with(|| {
+        assert!(!AssetGetterMock::currencies().contains(&other));
+        check_balances_aggregates(other, 500, 200, line!());
+        assert!(ModuleBalances::audit_aggregates().is_empty());
+    });
+}
+
+#[test]
+#[should_panic(expected = "Unknown currency in genesis balances")]
+fn test_genesis_unknown_currency() {
+    genesis_ext(vec![(1, Unknown, Positive(500))]);
+}
+
+#[test]
+#[should_panic(expected = "Duplicate genesis balance")]
+fn test_genesis_duplicate_balance() {
+    genesis_ext(vec![(1, Btc, Positive(500)), (1, Btc, Negative(100))]);
+}
+
+#[test]
+#[should_panic(expected = "Duplicate genesis balance")]
+fn test_genesis_duplicate_balance_of_built_in_id() {
+    let config: GenesisConfig<Test> = serde_json::from_str(
+        r#"{
+            "balances": [[1, "btc", {"Positive": 500}], [1, {"other": 4}, {"Positive": 100}]]
+        }"#,
+    )
+    .unwrap();
+    genesis_ext(config.balances);
+}
+
+#[test]
+fn test_genesis_config_serde() {
+    let config: GenesisConfig<Test> = serde_json::from_str(
+        r#"{
+            "balances": [[1, "btc", {"Positive": 500}], [2, "eq", {"Negative": 100}]]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.balances,
+        vec![(1, Btc, Positive(500)), (2, Eq, Negative(100))]
+    );
+    assert!(serde_json::from_str::<GenesisConfig<Test>>(
+        r#"{"balances": [[1, "xrp", {"Positive": 500}]]}"#
+    )
+    .is_err());
+}
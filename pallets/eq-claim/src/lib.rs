@@ -7,10 +7,7 @@ mod mock;
 mod secp_utils;
 
 use codec::{Decode, Encode};
-use eq_primitives::AccountGetter;
-use eq_utils::log::eq_log;
-#[allow(unused_imports)]
-use frame_support::debug;
+use eq_utils::log::eq_warn;
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::IsSubType,
@@ -25,7 +22,7 @@ use sp_io::{crypto::secp256k1_ecdsa_recover, hashing::keccak_256};
 #[cfg(feature = "std")]
 use sp_runtime::traits::Zero;
 use sp_runtime::{
-    traits::{CheckedSub, DispatchInfoOf, Saturating, SignedExtension},
+    traits::{CheckedSub, DispatchInfoOf, SignedExtension},
     transaction_validity::{
         InvalidTransaction, TransactionLongevity, TransactionSource, TransactionValidity,
         TransactionValidityError, ValidTransaction,
@@ -34,6 +31,8 @@ use sp_runtime::{
 };
 use sp_std::{fmt::Debug, prelude::*};
 
+const LOG_TARGET: &str = "eq_claim";
+
 pub trait WeightInfo {
     fn claim(u: u32) -> Weight;
     fn mint_claim(c: u32) -> Weight;
@@ -76,7 +75,6 @@ pub trait Trait: frame_system::Trait {
     type VestingSchedule: VestingSchedule<Self::AccountId, Moment = Self::BlockNumber>;
     type Prefix: Get<&'static [u8]>;
     type MoveClaimOrigin: EnsureOrigin<Self::Origin>;
-    type VestingAccountGetter: AccountGetter<Self::AccountId>;
     type WeightInfo: WeightInfo;
 }
 
@@ -308,7 +306,8 @@ decl_module! {
             ensure_root(origin)?;
 
             if vesting_schedule != None && value < vesting_schedule.unwrap().0 {
-                eq_log!(
+                eq_warn!(
+                    target: LOG_TARGET,
                     "mint_claim error: value {:?} < vesting_schedule.locked {:?}",
                     value,
                     vesting_schedule.unwrap().0
@@ -498,23 +497,14 @@ impl<T: Trait> Module<T> {
             return Err(Error::<T>::VestedBalanceExists.into());
         }
 
+        CurrencyOf::<T>::deposit_creating(&dest, balance_due);
+
         // Check if this claim should have a vesting schedule.
         if let Some(vs) = vesting {
-            let initial_balance = balance_due.saturating_sub(vs.0);
-            CurrencyOf::<T>::deposit_creating(&dest, initial_balance);
-            let vesting_account_id = T::VestingAccountGetter::get_account_id();
-
-            #[allow(unused_must_use)]
-            {
-                CurrencyOf::<T>::deposit_into_existing(&vesting_account_id, vs.0);
-            }
-
             // This can only fail if the account already has a vesting schedule,
             // but this is checked above.
             T::VestingSchedule::add_vesting_schedule(&dest, vs.0, vs.1, vs.2)
                 .expect("No other vesting schedule exists, as checked above; qed");
-        } else {
-            CurrencyOf::<T>::deposit_creating(&dest, balance_due);
         }
 
         <Total<T>>::put(new_total);
@@ -6,6 +6,7 @@ use crate::secp_utils::*;
 use crate::EthereumAddress;
 use codec::Encode;
 use eq_balances::currency;
+use eq_primitives::AccountGetter;
 use frame_support::{
     assert_err, assert_noop, assert_ok,
     dispatch::DispatchError::BadOrigin,
@@ -43,6 +44,8 @@ parameter_types! {
     pub const MaximumBlockLength: u32 = 4 * 1024 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
     pub const ExistentialDeposit: u64 = 1;
+    pub const MaxReserves: u32 = 50;
+    pub const MaxLocks: u32 = 50;
 }
 impl frame_system::Trait for Test {
     type BaseCallFilter = ();
@@ -81,31 +84,48 @@ impl eq_balances::BalanceChecker<u64, u64> for BalanceCheckerMock {
         who: &u64,
         currency: &currency::Currency,
         change: &eq_balances::SignedBalance<u64>,
-    ) -> bool {
-        let res = match change {
-            eq_balances::SignedBalance::Positive(_) => true,
+    ) -> DispatchResult {
+        match change {
+            eq_balances::SignedBalance::Positive(_) => Ok(()),
             eq_balances::SignedBalance::Negative(change_value) => {
                 let balance =
                     <Balances as eq_balances::BalanceGetter<u64, u64>>::get_balance(who, currency);
                 match balance {
-                    eq_balances::SignedBalance::Negative(_) => false,
-                    eq_balances::SignedBalance::Positive(balance_value) => {
-                        balance_value >= *change_value
+                    eq_balances::SignedBalance::Positive(balance_value)
+                        if balance_value >= *change_value =>
+                    {
+                        Ok(())
                     }
+                    _ => Err(eq_balances::Error::<Test>::InsufficientBalance.into()),
                 }
             }
-        };
-        res
+        }
+    }
+}
+
+pub struct TreasuryAccountGetter;
+impl AccountGetter<u64> for TreasuryAccountGetter {
+    fn get_account_id() -> u64 {
+        1000
     }
 }
 
 impl eq_balances::Trait for Test {
     type Balance = u64;
+    type Amount = i64;
     type ExistentialDeposit = ExistentialDeposit;
+    type TreasuryAccountGetter = TreasuryAccountGetter;
+    type MaxReserves = MaxReserves;
+    type MaxLocks = MaxLocks;
+    type FreezeOrigin = frame_system::EnsureRoot<u64>;
+    type CheckpointPeriod = ();
+    type CheckpointRetention = ();
+    type MaxCheckpointsPruned = ();
     type BalanceChecker = BalanceCheckerMock;
     type Event = ();
     type TotalIssuance = ();
     type BalanceGetter = ModuleBalances;
+    type AssetGetter = ();
     type WeightInfo = ();
 }
 parameter_types! {
@@ -137,7 +157,6 @@ impl Trait for Test {
     type VestingSchedule = Vesting;
     type Prefix = Prefix;
     type MoveClaimOrigin = frame_system::EnsureSignedBy<Six, u64>;
-    type VestingAccountGetter = Vesting;
     type WeightInfo = ();
 }
 // type System = frame_system::Module<Test>; // fix
@@ -220,7 +239,7 @@ fn claiming_works() {
             42,
             sig::<Test>(&alice(), &42u64.encode(), &[][..])
         ));
-        assert_eq!(BasicCurrency::free_balance(&42), 50);
+        assert_eq!(BasicCurrency::free_balance(&42), 100);
         assert_eq!(Vesting::vesting_balance(&42), Some(50));
         assert_eq!(Claims::total(), total_claims() - 100);
     });
@@ -253,8 +272,7 @@ fn basic_claim_moving_works() {
             42,
             sig::<Test>(&bob(), &42u64.encode(), &[][..])
         ));
-        assert_eq!(BasicCurrency::free_balance(&42), 50);
-        assert_eq!(BasicCurrency::free_balance(&Vesting::account_id()), 50);
+        assert_eq!(BasicCurrency::free_balance(&42), 100);
         assert_eq!(Vesting::vesting_balance(&42), Some(50));
         assert_eq!(Claims::total(), total_claims() - 100);
     });
@@ -394,15 +412,14 @@ fn claim_cannot_clobber_preclaim() {
             42,
             sig::<Test>(&alice(), &42u64.encode(), &[][..])
         ));
-        assert_eq!(BasicCurrency::free_balance(&42), 50);
-        assert_eq!(BasicCurrency::free_balance(&Vesting::account_id()), 50);
+        assert_eq!(BasicCurrency::free_balance(&42), 100);
         assert_eq!(Vesting::vesting_balance(&42), Some(50));
         // Eve's claim is 300 through Account 42
         assert_ok!(Claims::attest(
             Origin::signed(42),
             get_statement_text().to_vec()
         ));
-        assert_eq!(BasicCurrency::free_balance(&42), 50 + 300);
+        assert_eq!(BasicCurrency::free_balance(&42), 100 + 300);
         assert_eq!(Claims::total(), total_claims() - 400);
     });
 }
@@ -513,14 +530,13 @@ fn add_claim_with_vesting_works() {
             69,
             sig::<Test>(&bob(), &69u64.encode(), &[][..])
         ));
-        assert_eq!(BasicCurrency::free_balance(&69), 150);
-        assert_eq!(BasicCurrency::free_balance(&Vesting::account_id()), 50);
+        assert_eq!(BasicCurrency::free_balance(&69), 200);
         assert_eq!(Vesting::vesting_balance(&69), Some(50));
 
         // Make sure we can not transfer the vested balance.
         assert_err!(
             BasicCurrency::transfer(&69, &80, 180, ExistenceRequirement::AllowDeath),
-            eq_balances::Error::<Test>::NotAllowedToChangeBalance
+            eq_balances::Error::<Test>::LiquidityRestrictions
         );
     });
 }
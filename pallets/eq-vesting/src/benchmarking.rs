@@ -37,7 +37,7 @@ fn add_locks<T: Trait>(who: &T::AccountId, n: u8) {
 		let lock_id = [id; 8];
 		let locked = 100;
 		let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
-		// T::Currency::set_lock(lock_id, who, locked.into(), reasons);
+		T::Currency::set_lock(lock_id, who, locked.into(), reasons);
 	}
 }
 
@@ -1,10 +1,12 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::traits::{Currency, ExistenceRequirement, Get, VestingSchedule};
-use frame_support::{
-    debug, decl_error, decl_event, decl_module, decl_storage, ensure, weights::Weight,
+use frame_support::storage::IterableStorageMap;
+use frame_support::traits::{
+    Currency, ExistenceRequirement, Get, LockIdentifier, LockableCurrency, VestingSchedule,
+    WithdrawReason, WithdrawReasons,
 };
+use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure, weights::Weight};
 use frame_system::{ensure_root, ensure_signed};
 use sp_runtime::{
     traits::{
@@ -20,7 +22,10 @@ mod benchmarking;
 mod benchmarks;
 mod mock;
 mod tests;
-use eq_utils::log::eq_log;
+use eq_utils::log::{eq_debug, eq_error};
+
+const VESTING_ID: LockIdentifier = *b"vesting ";
+const LOG_TARGET: &str = "eq_vesting";
 
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
@@ -37,10 +42,11 @@ pub trait Trait: frame_system::Trait {
     /// The overarching event type.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 
+    /// Account that escrowed vesting funds before they were locked in place
     type ModuleId: Get<ModuleId>;
 
     /// The currency adapter trait.
-    type Currency: Currency<Self::AccountId>;
+    type Currency: LockableCurrency<Self::AccountId, Moment = Self::BlockNumber>;
 
     /// Convert the block number into a balance.
     type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
@@ -102,6 +108,21 @@ impl<Balance: AtLeast32BitUnsigned + Copy, BlockNumber: AtLeast32BitUnsigned + C
     }
 }
 
+/// Storage layout versions of the module
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Releases {
+    /// Vesting funds are kept on the module account
+    V1EscrowAccount,
+    /// Vesting funds are locked on the vesting account
+    V2Locks,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1EscrowAccount
+    }
+}
+
 decl_storage! {
     trait Store for Module<T: Trait> as Vesting {
         /// Information regarding the vesting of a given account.
@@ -109,9 +130,14 @@ decl_storage! {
             map hasher(blake2_128_concat) T::AccountId
             => Option<VestingInfo<BalanceOf<T>, T::BlockNumber>>;
 
+        /// Amount already released from the module account, only read by the migration
+        /// to locks
         pub Vested get(fn vested):
             map hasher(blake2_128_concat) T::AccountId
             => Option<BalanceOf<T>>;
+
+        /// Storage layout version, new chains start with locks
+        StorageVersion build(|_: &GenesisConfig<T>| Releases::V2Locks): Releases;
     }
     add_extra_genesis {
         config(vesting): Vec<(T::AccountId, T::BlockNumber, T::BlockNumber, BalanceOf<T>)>;
@@ -135,7 +161,8 @@ decl_storage! {
                     per_block: per_block,
                     starting_block: begin
                 });
-                // let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+                let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+                T::Currency::set_lock(VESTING_ID, who, locked, reasons);
             }
         })
     }
@@ -260,7 +287,7 @@ decl_module! {
             let who = T::Lookup::lookup(target)?;
             ensure!(!Vesting::<T>::contains_key(&who), Error::<T>::ExistingVestingSchedule);
 
-            T::Currency::transfer(&transactor, &Self::account_id(), schedule.locked, ExistenceRequirement::AllowDeath)?;
+            T::Currency::transfer(&transactor, &who, schedule.locked, ExistenceRequirement::AllowDeath)?;
 
             Self::add_vesting_schedule(&who, schedule.locked, schedule.per_block, schedule.starting_block)
                 .expect("user does not have an existing vesting schedule; q.e.d.");
@@ -301,7 +328,7 @@ decl_module! {
             let source = T::Lookup::lookup(source)?;
             ensure!(!Vesting::<T>::contains_key(&target), Error::<T>::ExistingVestingSchedule);
 
-            T::Currency::transfer(&source, &Self::account_id(), schedule.locked, ExistenceRequirement::AllowDeath)?;
+            T::Currency::transfer(&source, &target, schedule.locked, ExistenceRequirement::AllowDeath)?;
 
             Self::add_vesting_schedule(&target, schedule.locked, schedule.per_block, schedule.starting_block)
                 .expect("user does not have an existing vesting schedule; q.e.d.");
@@ -320,36 +347,70 @@ impl<T: Trait> Module<T> {
     fn update_lock(who: T::AccountId) -> DispatchResult {
         let vesting = Self::vesting(&who).ok_or(Error::<T>::NotVesting)?;
         let now = <frame_system::Module<T>>::block_number();
-        let unlocked_now = vesting.unlocked_at::<T::BlockNumberToBalance>(now);
-        let vested = Self::vested(&who).unwrap_or(BalanceOf::<T>::zero());
-        let to_vest = unlocked_now.saturating_sub(vested);
-        eq_log!(
-            "vest() who: {:?}, to_vest: {:?}, now: {:?}",
+        let locked_now = vesting.locked_at::<T::BlockNumberToBalance>(now);
+        eq_debug!(
+            target: LOG_TARGET,
+            "vest() who: {:?}, locked_now: {:?}, now: {:?}",
             &who,
-            &to_vest,
+            &locked_now,
             &now
         );
 
-        #[allow(unused_must_use)]
-        if to_vest > BalanceOf::<T>::zero() {
-            T::Currency::transfer(
-                &Self::account_id(),
-                &who,
-                to_vest,
-                ExistenceRequirement::KeepAlive,
-            );
-
-            if unlocked_now == vesting.locked {
-                Vesting::<T>::remove(&who);
-                Vested::<T>::remove(&who);
-                Self::deposit_event(RawEvent::VestingCompleted(who));
-            } else {
-                Vested::<T>::insert(&who, unlocked_now);
-                Self::deposit_event(RawEvent::VestingUpdated(who, to_vest));
-            }
-        };
+        if locked_now.is_zero() {
+            T::Currency::remove_lock(VESTING_ID, &who);
+            Vesting::<T>::remove(&who);
+            Self::deposit_event(RawEvent::VestingCompleted(who));
+        } else {
+            let reasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+            T::Currency::set_lock(VESTING_ID, &who, locked_now, reasons);
+            Self::deposit_event(RawEvent::VestingUpdated(who, locked_now));
+        }
         Ok(())
     }
+
+    /// Runs the pending storage migration. Not a module hook: escrowed funds are moved
+    /// with `T::Currency`, so the runtime calls it from its `OnRuntimeUpgrade` after the
+    /// currency module is migrated.
+    pub fn migrate() -> Weight {
+        if StorageVersion::get() == Releases::V1EscrowAccount {
+            Self::migrate_escrow_to_locks()
+        } else {
+            T::DbWeight::get().reads(1)
+        }
+    }
+
+    /// Returns funds still escrowed on the module account to vesting accounts and locks
+    /// them in place
+    fn migrate_escrow_to_locks() -> Weight {
+        let now = <frame_system::Module<T>>::block_number();
+        let reasons: WithdrawReasons = WithdrawReason::Transfer | WithdrawReason::Reserve;
+        let mut count: Weight = 0;
+        for (who, vesting) in Vesting::<T>::iter() {
+            let vested = Vested::<T>::take(&who).unwrap_or_else(Zero::zero);
+            let escrowed = vesting.locked.saturating_sub(vested);
+            if !escrowed.is_zero() {
+                if let Err(e) = T::Currency::transfer(
+                    &Self::account_id(),
+                    &who,
+                    escrowed,
+                    ExistenceRequirement::AllowDeath,
+                ) {
+                    eq_error!(
+                        target: LOG_TARGET,
+                        "vesting migration failed for {:?}: {:?}",
+                        &who,
+                        e
+                    );
+                }
+            }
+            let locked_now = vesting.locked_at::<T::BlockNumberToBalance>(now);
+            T::Currency::set_lock(VESTING_ID, &who, locked_now, reasons);
+            count += 1;
+        }
+        StorageVersion::put(Releases::V2Locks);
+
+        T::DbWeight::get().reads_writes(count * 4 + 1, count * 5 + 1)
+    }
 }
 
 impl<T: Trait> VestingSchedule<T::AccountId> for Module<T>
@@ -406,8 +467,7 @@ where
     /// Remove a vesting schedule for a given account.
     fn remove_vesting_schedule(who: &T::AccountId) {
         Vesting::<T>::remove(who);
-        // it can't fail, but even if somehow it did, we don't really care.
-        let _ = Self::update_lock(who.clone());
+        T::Currency::remove_lock(VESTING_ID, who);
     }
 }
 
@@ -22,6 +22,8 @@ parameter_types! {
     pub const MaximumBlockLength: u32 = 2 * 1024;
     pub const AvailableBlockRatio: Perbill = Perbill::one();
     pub const ExistentialDeposit: u64 = 1;
+    pub const MaxReserves: u32 = 50;
+    pub const MaxLocks: u32 = 50;
 }
 impl frame_system::Trait for Test {
     type BaseCallFilter = ();
@@ -53,13 +55,29 @@ impl frame_system::Trait for Test {
 
 thread_local! {}
 
+pub struct TreasuryAccountGetter;
+impl eq_primitives::AccountGetter<u64> for TreasuryAccountGetter {
+    fn get_account_id() -> u64 {
+        1000
+    }
+}
+
 impl eq_balances::Trait for Test {
     type Balance = u64;
+    type Amount = i64;
     type ExistentialDeposit = ExistentialDeposit;
+    type TreasuryAccountGetter = TreasuryAccountGetter;
+    type MaxReserves = MaxReserves;
+    type MaxLocks = MaxLocks;
+    type FreezeOrigin = frame_system::EnsureRoot<u64>;
+    type CheckpointPeriod = ();
+    type CheckpointRetention = ();
+    type MaxCheckpointsPruned = ();
     type BalanceChecker = ();
     type Event = ();
     type TotalIssuance = ();
     type BalanceGetter = ModuleBalances;
+    type AssetGetter = ();
     type WeightInfo = ();
 }
 parameter_types! {
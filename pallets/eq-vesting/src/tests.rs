@@ -1,23 +1,35 @@
 #![cfg(test)]
 
 use super::Error;
-use crate::mock::{new_test_ext, ModuleBalances, ModuleVesting, Origin, System, Test};
+use crate::mock::{
+    new_test_ext, BasicCurrency, ModuleBalances, ModuleVesting, Origin, System, Test,
+};
 use eq_balances::{currency, BalanceGetter, BalanceSetter, SignedBalance};
 use eq_utils::fx64;
+use frame_support::traits::{Currency, ExistenceRequirement};
 use frame_support::{assert_err, assert_ok};
 use sp_arithmetic::{FixedI64, FixedPointNumber};
 
 fn set_pos_balance_with_agg_unsafe(who: &u64, currency: &currency::Currency, amount: FixedI64) {
     let balance = SignedBalance::Positive(amount.into_inner() as u64);
-    <ModuleBalances as BalanceSetter<u64, u64>>::set_balance_with_agg_unsafe(
-        who, currency, balance,
+    assert_ok!(
+        <ModuleBalances as BalanceSetter<u64, u64>>::set_balance_with_agg_unsafe(
+            who, currency, balance,
+        )
     );
 }
 
+fn locked(who: &u64) -> u64 {
+    ModuleBalances::frozen_balance(
+        currency::Currency::Eq,
+        who,
+        frame_support::traits::WithdrawReason::Transfer.into(),
+    )
+}
+
 #[test]
 fn vested_transfer_amount_low() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
         assert_err!(
@@ -39,13 +51,7 @@ fn vested_transfer_amount_low() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(100, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(0, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(0)
@@ -56,7 +62,6 @@ fn vested_transfer_amount_low() {
 #[test]
 fn vested_transfer_ok() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -79,16 +84,10 @@ fn vested_transfer_ok() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(10, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(0)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
     });
 }
@@ -96,7 +95,6 @@ fn vested_transfer_ok() {
 #[test]
 fn vested_transfer_already_exists() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
         assert_ok!(ModuleVesting::vested_transfer(
@@ -127,16 +125,10 @@ fn vested_transfer_already_exists() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(10, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(0)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
     });
 }
@@ -144,7 +136,6 @@ fn vested_transfer_already_exists() {
 #[test]
 fn vest_no_vesting() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
 
         assert_err!(
@@ -158,13 +149,7 @@ fn vest_no_vesting() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&1), fx64!(0, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(0)
@@ -175,7 +160,6 @@ fn vest_no_vesting() {
 #[test]
 fn vest_before_start() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -201,16 +185,10 @@ fn vest_before_start() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(10, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
     });
 }
@@ -218,7 +196,6 @@ fn vest_before_start() {
 #[test]
 fn vest_temp() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -244,16 +221,10 @@ fn vest_temp() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(9, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(9, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(1, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
     });
 }
@@ -261,7 +232,6 @@ fn vest_temp() {
 #[test]
 fn vest_temp2() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -287,16 +257,10 @@ fn vest_temp2() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(9, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(9, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(1, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
 
         System::set_block_number(13);
@@ -307,16 +271,10 @@ fn vest_temp2() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(7, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(7, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(3, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
     });
 }
@@ -324,7 +282,6 @@ fn vest_temp2() {
 #[test]
 fn vest_all() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -350,16 +307,10 @@ fn vest_all() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(9, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(9, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(1, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
         );
 
         System::set_block_number(21);
@@ -370,13 +321,7 @@ fn vest_all() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
-        assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
-            ),
-            eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
-        );
+        assert_eq!(locked(&2), fx64!(0, 0).into_inner() as u64);
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
@@ -388,7 +333,6 @@ fn vest_all() {
 #[test]
 fn vest_all_init() {
     new_test_ext().execute_with(|| {
-        let module_account_id = ModuleVesting::account_id();
         let account_id = 1;
         set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
 
@@ -410,17 +354,65 @@ fn vest_all_init() {
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&1, &currency::Currency::Eq),
             eq_balances::SignedBalance::Positive(fx64!(90, 0).into_inner() as u64)
         );
+        assert_eq!(locked(&2), fx64!(0, 0).into_inner() as u64);
         assert_eq!(
-            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(
-                &module_account_id,
-                &currency::Currency::Eq
+            <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
+            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
+        );
+        assert_eq!(ModuleVesting::vesting(2), Option::None);
+    });
+}
+
+#[test]
+fn vesting_funds_are_locked() {
+    new_test_ext().execute_with(|| {
+        let account_id = 1;
+        set_pos_balance_with_agg_unsafe(&account_id, &currency::Currency::Eq, fx64!(100, 0));
+
+        System::set_block_number(1);
+
+        let vesting_info = super::VestingInfo {
+            locked: fx64!(10, 0).into_inner() as u64,
+            per_block: fx64!(1, 0).into_inner() as u64,
+            starting_block: 10,
+        };
+
+        assert_ok!(ModuleVesting::vested_transfer(
+            Origin::signed(account_id),
+            2,
+            vesting_info
+        ));
+        assert_err!(
+            BasicCurrency::transfer(
+                &2,
+                &3,
+                fx64!(1, 0).into_inner() as u64,
+                ExistenceRequirement::AllowDeath
             ),
-            eq_balances::SignedBalance::Positive(fx64!(0, 0).into_inner() as u64)
+            eq_balances::Error::<Test>::LiquidityRestrictions
         );
+
+        System::set_block_number(11);
+        assert_ok!(ModuleVesting::vest(Origin::signed(2),));
+
+        assert_err!(
+            BasicCurrency::transfer(
+                &2,
+                &3,
+                fx64!(2, 0).into_inner() as u64,
+                ExistenceRequirement::AllowDeath
+            ),
+            eq_balances::Error::<Test>::LiquidityRestrictions
+        );
+        assert_ok!(BasicCurrency::transfer(
+            &2,
+            &3,
+            fx64!(1, 0).into_inner() as u64,
+            ExistenceRequirement::AllowDeath
+        ));
         assert_eq!(
             <ModuleBalances as BalanceGetter<u64, u64>>::get_balance(&2, &currency::Currency::Eq),
-            eq_balances::SignedBalance::Positive(fx64!(10, 0).into_inner() as u64)
+            eq_balances::SignedBalance::Positive(fx64!(9, 0).into_inner() as u64)
         );
-        assert_eq!(ModuleVesting::vesting(2), Option::None);
     });
 }
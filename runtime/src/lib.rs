@@ -8,6 +8,7 @@ include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
 use crate::sp_api_hidden_includes_construct_runtime::hidden_include::sp_runtime::traits::SaturatedConversion;
 use codec::Encode;
+pub use eq_assets;
 pub use eq_balances;
 pub use eq_claim::EthereumAddress;
 pub use eq_distribution;
@@ -35,8 +36,8 @@ use sp_runtime::transaction_validity::{TransactionSource, TransactionValidity};
 #[cfg(any(feature = "std", test))]
 pub use sp_runtime::BuildStorage;
 use sp_runtime::{
-    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, ModuleId, MultiSignature,
-    PerThing, Perquintill,
+    create_runtime_str, generic, impl_opaque_keys, ApplyExtrinsicResult, DispatchResult, ModuleId,
+    MultiSignature, PerThing, Perquintill,
 };
 pub use sp_runtime::{Perbill, Permill};
 use sp_std::prelude::*;
@@ -51,10 +52,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("Equilibrium"),
     impl_name: create_runtime_str!("Equilibrium"),
     authoring_version: 10,
-    spec_version: 257,
+    spec_version: 258,
     impl_version: 0,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 /// The version information used to identify this runtime when compiled natively.
@@ -222,6 +223,11 @@ impl timestamp::Trait for Runtime {
 
 parameter_types! {
     pub const ExistentialDeposit: Balance = 0;
+    pub const MaxReserves: u32 = 50;
+    pub const MaxLocks: u32 = 50;
+    pub const CheckpointPeriod: BlockNumber = DAYS;
+    pub const CheckpointRetention: BlockNumber = 90 * DAYS;
+    pub const MaxCheckpointsPruned: u32 = 100;
     pub const TotalIssuence: Balance = 0;
     pub const BasicCurrencyGet: eq_primitives::currency::Currency = eq_primitives::currency::Currency::Eq;
 }
@@ -234,38 +240,54 @@ impl eq_balances::BalanceChecker<Balance, AccountId> for BalanceChecker {
         who: &AccountId,
         currency: &eq_primitives::currency::Currency,
         change: &SignedBalance,
-    ) -> bool {
-        let res = match change {
-            SignedBalance::Positive(_) => true,
+    ) -> DispatchResult {
+        match change {
+            SignedBalance::Positive(_) => Ok(()),
             SignedBalance::Negative(change_value) => {
                 let balance =
                     <Balances as eq_balances::BalanceGetter<AccountId, Balance>>::get_balance(
                         who, currency,
                     );
                 match balance {
-                    SignedBalance::Negative(_) => false,
-                    SignedBalance::Positive(balance_value) => balance_value >= *change_value,
+                    SignedBalance::Positive(balance_value) if balance_value >= *change_value => {
+                        Ok(())
+                    }
+                    _ => Err(eq_balances::Error::<Runtime>::InsufficientBalance.into()),
                 }
             }
-        };
-        res
+        }
     }
 }
 
 impl eq_balances::Trait for Runtime {
     /// The type for recording an account's balance.
     type Balance = Balance;
+    type Amount = i128;
     /// The ubiquitous event type.
     type Event = Event;
 
     type BalanceGetter = eq_balances::Module<Runtime>;
     type BalanceChecker = BalanceChecker;
+    type AssetGetter = EqAssets;
 
     type ExistentialDeposit = ExistentialDeposit;
+    type TreasuryAccountGetter = EqTreasury;
+    type MaxReserves = MaxReserves;
+    type MaxLocks = MaxLocks;
+    type FreezeOrigin = system::EnsureRoot<AccountId>;
+    type CheckpointPeriod = CheckpointPeriod;
+    type CheckpointRetention = CheckpointRetention;
+    type MaxCheckpointsPruned = MaxCheckpointsPruned;
     type TotalIssuance = TotalIssuence;
     type WeightInfo = ();
 }
 
+impl eq_assets::Trait for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type WeightInfo = ();
+}
+
 pub type BasicCurrency = eq_balances::balance_adapter::BalanceAdapter<
     Runtime,
     eq_balances::Module<Runtime>,
@@ -285,12 +307,13 @@ const_assert!(
                 / <Perbill as PerThing>::ACCURACY as <Perquintill as PerThing>::Inner)
 );
 
-type EqImbalance = eq_balances::NegativeImbalance<Balance>;
+type EqImbalance = eq_balances::NegativeImbalance<Runtime>;
 
 pub struct DealWithFees;
 impl frame_support::traits::OnUnbalanced<EqImbalance> for DealWithFees {
     fn on_unbalanceds<B>(mut fees_then_tips: impl Iterator<Item = EqImbalance>) {
         if let Some(fees) = fees_then_tips.next() {
+            let currency = fees.currency();
             // for fees, 20% to treasury, 80% to author
             let mut split = fees.ration(20, 80);
             if let Some(tips) = fees_then_tips.next() {
@@ -299,13 +322,13 @@ impl frame_support::traits::OnUnbalanced<EqImbalance> for DealWithFees {
             }
 
             <Balances as eq_balances::EqCurrency<AccountId, Balance>>::resolve_creating(
-                eq_balances::currency::Currency::Eq,
+                currency,
                 &Authorship::author(),
                 split.1,
             );
 
             <Balances as eq_balances::EqCurrency<AccountId, Balance>>::resolve_creating(
-                eq_balances::currency::Currency::Eq,
+                currency,
                 &EqTreasury::account_id(),
                 split.0,
             );
@@ -357,7 +380,6 @@ impl eq_claim::Trait for Runtime {
     type VestingSchedule = eq_vesting::Module<Runtime>;
     type Prefix = Prefix;
     type MoveClaimOrigin = system::EnsureNever<Self::AccountId>;
-    type VestingAccountGetter = eq_vesting::Module<Runtime>;
     type WeightInfo = ();
 }
 
@@ -462,7 +484,8 @@ construct_runtime!(
         EqTreasury: eq_distribution::<Instance1>::{Module, Storage},
         EqParachainOffering: eq_distribution::<Instance2>::{Module, Storage},
         EqVesting: eq_vesting::{Module, Call, Storage, Event<T>, Config<T>},
-        Claim: eq_claim::{Module, Call, Storage, Event<T>, Config<T>, ValidateUnsigned}
+        Claim: eq_claim::{Module, Call, Storage, Event<T>, Config<T>, ValidateUnsigned},
+        EqAssets: eq_assets::{Module, Call, Storage, Config<T>, Event}
     }
 );
 
@@ -493,9 +516,35 @@ pub type SignedPayload = generic::SignedPayload<Call, SignedExtra>;
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
+/// Storage migrations of the modules in the order they depend on each other: vesting
+/// moves escrowed funds through `Balances`, so balances are migrated first.
+pub struct OrderedMigrations;
+impl frame_support::traits::OnRuntimeUpgrade for OrderedMigrations {
+    fn on_runtime_upgrade() -> Weight {
+        Balances::migrate().saturating_add(EqVesting::migrate())
+    }
+}
+
 /// Executive: handles dispatch to the various modules.
-pub type Executive =
-    frame_executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
+pub type Executive = frame_executive::Executive<
+    Runtime,
+    Block,
+    system::ChainContext<Runtime>,
+    Runtime,
+    AllModules,
+    OrderedMigrations,
+>;
+
+/// Installs the wasm logger for the `eq_*` log macros of the pallets, native runtime
+/// uses the node's logger
+#[cfg(all(feature = "runtime-logging", not(feature = "std")))]
+fn init_logger() {
+    debug::RuntimeLogger::init();
+    log::set_max_level(log::LevelFilter::Trace);
+}
+
+#[cfg(not(all(feature = "runtime-logging", not(feature = "std"))))]
+fn init_logger() {}
 
 impl_runtime_apis! {
     impl sp_api::Core<Block> for Runtime {
@@ -504,10 +553,12 @@ impl_runtime_apis! {
         }
 
         fn execute_block(block: Block) {
+            init_logger();
             Executive::execute_block(block)
         }
 
         fn initialize_block(header: &<Block as BlockT>::Header) {
+            init_logger();
             Executive::initialize_block(header)
         }
     }
@@ -520,10 +571,12 @@ impl_runtime_apis! {
 
     impl sp_block_builder::BlockBuilder<Block> for Runtime {
         fn apply_extrinsic(extrinsic: <Block as BlockT>::Extrinsic) -> ApplyExtrinsicResult {
+            init_logger();
             Executive::apply_extrinsic(extrinsic)
         }
 
         fn finalize_block() -> <Block as BlockT>::Header {
+            init_logger();
             Executive::finalize_block()
         }
 
@@ -551,12 +604,14 @@ impl_runtime_apis! {
 
     impl sp_transaction_pool::runtime_api::TaggedTransactionQueue<Block> for Runtime {
         fn validate_transaction(_source: TransactionSource, tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+            init_logger();
             Executive::validate_transaction(_source,  tx)
         }
     }
 
     impl sp_offchain::OffchainWorkerApi<Block> for Runtime {
         fn offchain_worker(header: &<Block as BlockT>::Header) {
+            init_logger();
             Executive::offchain_worker(header)
         }
     }
@@ -580,6 +635,35 @@ impl_runtime_apis! {
         }
     }
 
+    impl eq_balances_rpc_runtime_api::EqBalancesApi<
+        Block,
+        AccountId,
+        Balance,
+        BlockNumber,
+    > for Runtime {
+        fn balance(who: AccountId, currency: eq_balances::currency::Currency) -> SignedBalance {
+            <Balances as eq_balances::BalanceGetter<AccountId, Balance>>::get_balance(&who, &currency)
+        }
+
+        fn all_balances(who: AccountId) -> Vec<(eq_balances::currency::Currency, SignedBalance)> {
+            Balances::balances_of(&who)
+        }
+
+        fn aggregates(
+            currency: eq_balances::currency::Currency,
+        ) -> eq_balances::BalancesAggregate<Balance> {
+            Balances::balances_aggregates(&currency)
+        }
+
+        fn balance_at(
+            who: AccountId,
+            currency: eq_balances::currency::Currency,
+            block: BlockNumber,
+        ) -> Option<SignedBalance> {
+            Balances::balance_at(&who, &currency, block)
+        }
+    }
+
     impl sp_session::SessionKeys<Block> for Runtime {
         fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
             opaque::SessionKeys::generate(seed)
@@ -641,6 +725,7 @@ impl_runtime_apis! {
                 &vec![],
             );
 
+            add_benchmark!(params, batches, eq_assets, EqAssets);
             add_benchmark!(params, batches, eq_balances, Balances);
             add_benchmark!(params, batches, eq_claim, Claim);
             add_benchmark!(params, batches, eq_vesting, EqVesting);